@@ -0,0 +1,281 @@
+//! Data Format Converter
+mod exit;
+
+use clap::Clap;
+use df_utils::*;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Supported data formats
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Format {
+    Csv,
+    Env,
+    Ini,
+    Json,
+    Json5,
+    Line,
+    Msgpack,
+    Ndjson,
+    Properties,
+    Toml,
+    Tsv,
+    Xml,
+    Yaml,
+}
+
+impl FromStr for Format {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(Self::Csv),
+            "env" => Ok(Self::Env),
+            "ini" => Ok(Self::Ini),
+            "json" => Ok(Self::Json),
+            "json5" => Ok(Self::Json5),
+            "line" => Ok(Self::Line),
+            "msgpack" => Ok(Self::Msgpack),
+            "ndjson" => Ok(Self::Ndjson),
+            "properties" => Ok(Self::Properties),
+            "toml" => Ok(Self::Toml),
+            "tsv" => Ok(Self::Tsv),
+            "xml" => Ok(Self::Xml),
+            "yaml" => Ok(Self::Yaml),
+            _ => Err(format!("Unknown format: {}", s)),
+        }
+    }
+}
+
+impl Format {
+    /// Guesses the format from the extension of the path.
+    fn from_path(path: &Path) -> Option<Self> {
+        // Dotenv files are usually named just `.env`.
+        if path.file_name()? == ".env" {
+            return Some(Self::Env);
+        }
+        match path.extension()?.to_str()? {
+            "csv" => Some(Self::Csv),
+            "env" => Some(Self::Env),
+            "ini" => Some(Self::Ini),
+            "json" => Some(Self::Json),
+            "json5" => Some(Self::Json5),
+            "msgpack" | "mpk" => Some(Self::Msgpack),
+            "ndjson" | "jsonl" => Some(Self::Ndjson),
+            "properties" => Some(Self::Properties),
+            "toml" => Some(Self::Toml),
+            "tsv" => Some(Self::Tsv),
+            "xml" => Some(Self::Xml),
+            "yaml" | "yml" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+}
+
+/// Options
+#[derive(Clone, Debug, Clap)]
+#[clap(
+    name = "dfconv",
+    version = clap::crate_version!(),
+    author = clap::crate_authors!(),
+    about = "Simple CLI Data Format Converter"
+)]
+struct Opts {
+    #[clap(name = "INPUT", about = "The input file.")]
+    input: Option<PathBuf>,
+    #[clap(short = 'o', long = "output", about = "The output file.")]
+    output: Option<PathBuf>,
+    #[clap(
+        short = 'f',
+        long = "from",
+        about = "The input format, guessed from the extension if omitted.",
+        possible_values = &[
+            "csv", "env", "ini", "json", "json5", "line", "msgpack", "ndjson", "properties",
+            "toml", "tsv", "xml", "yaml"
+        ]
+    )]
+    from: Option<Format>,
+    #[clap(
+        short = 't',
+        long = "to",
+        about = "The output format.",
+        possible_values = &[
+            "csv", "env", "ini", "json", "json5", "line", "msgpack", "ndjson", "properties",
+            "toml", "tsv", "xml", "yaml"
+        ]
+    )]
+    to: Format,
+    #[clap(
+        short = 'r',
+        long = "root",
+        about = "The root indicator.",
+        default_value = "$"
+    )]
+    root: String,
+    #[clap(
+        short = 'd',
+        long = "delimiter",
+        about = "The delimiter for the path.",
+        default_value = "."
+    )]
+    delimiter: String,
+    #[clap(
+        short = 'e',
+        long = "equal",
+        about = "The equal symbol.",
+        default_value = " = "
+    )]
+    equal: String,
+    #[clap(
+        short = 'b',
+        long = "bracket-paths",
+        about = "Use paths like `$.users[0][\"first.name\"]`."
+    )]
+    bracket_paths: bool,
+    #[clap(
+        short = 'l',
+        long = "lenient",
+        about = "Let later lines win over conflicting ones, filling gaps by null."
+    )]
+    lenient: bool,
+    #[clap(
+        short = 'k',
+        long = "expand-keys",
+        about = "Expand dotted keys of properties like `a.b.0` into nested maps and arrays, and quote keys to be expanded back."
+    )]
+    expand_keys: bool,
+    #[clap(
+        short = 'I',
+        long = "infer-types",
+        about = "Read CSV fields like `true`, `1.5` and empty ones as booleans, numbers and null."
+    )]
+    infer_types: bool,
+    #[clap(
+        short = 'a',
+        long = "always-array",
+        about = "Wrap child elements of XML in arrays even if they are not repeated."
+    )]
+    always_array: bool,
+    #[clap(
+        short = 'x',
+        long = "export",
+        about = "Prefix lines of dotenv output by `export`."
+    )]
+    export: bool,
+    #[clap(short = 'm', long = "minify", about = "Minify JSON and XML output.")]
+    minify: bool,
+    #[clap(
+        short = 'i',
+        long = "indent",
+        about = "The indent size, 4 for JSON and 2 for XML and YAML by default.",
+        conflicts_with = "minify"
+    )]
+    indent: Option<usize>,
+}
+
+fn main() {
+    let opts: Opts = exit::parse_args();
+
+    let from = opts
+        .from
+        .or_else(|| opts.input.as_deref().and_then(Format::from_path))
+        .unwrap_or_else(|| {
+            exit::Failure::Usage.fail("Can't guess the input format, specify it by `--from`.")
+        });
+
+    let source = io::Source::from_path(opts.input.as_ref()).unwrap_or_else(exit::io_error);
+    let value = parse(from, &opts, &source.bytes).unwrap_or_else(|e| {
+        source.report(&e);
+        exit::Failure::Parse.exit()
+    });
+
+    let output = opts.output.as_ref();
+    match opts.to {
+        Format::Csv | Format::Tsv => exit::write(
+            generator::CsvGenerator::new().delimiter(if opts.to == Format::Tsv {
+                '\t'
+            } else {
+                ','
+            }),
+            output,
+            &value,
+        ),
+        Format::Env => exit::write(
+            generator::DotenvGenerator::new().export(opts.export),
+            output,
+            &value,
+        ),
+        Format::Ini => exit::write(&generator::IniGenerator, output, &value),
+        // JSON is also valid as JSON5.
+        Format::Json | Format::Json5 if opts.minify => {
+            exit::write(&generator::MinJsonGenerator::new(), output, &value)
+        }
+        Format::Json | Format::Json5 => exit::write(
+            generator::PrettyJsonGenerator::new().indent(opts.indent.unwrap_or(4)),
+            output,
+            &value,
+        ),
+        Format::Msgpack => exit::write(&generator::MsgpackGenerator, output, &value),
+        Format::Ndjson => exit::write(&generator::NdjsonGenerator::new(), output, &value),
+        Format::Properties => exit::write(
+            generator::PropertiesGenerator::new().expand_keys(opts.expand_keys),
+            output,
+            &value,
+        ),
+        Format::Toml => exit::write(&generator::TomlGenerator, output, &value),
+        Format::Xml => exit::write(
+            generator::XmlGenerator::new().indent(if opts.minify {
+                0
+            } else {
+                opts.indent.unwrap_or(2)
+            }),
+            output,
+            &value,
+        ),
+        Format::Yaml => exit::write(
+            generator::YamlGenerator::new().indent(opts.indent.unwrap_or(2)),
+            output,
+            &value,
+        ),
+        Format::Line => exit::write(
+            generator::LineGenerator::new()
+                .root(&opts.root)
+                .delimiter(&opts.delimiter)
+                .equal(&opts.equal)
+                .bracket_paths(opts.bracket_paths),
+            output,
+            &value,
+        ),
+    }
+}
+
+/// Parses the input in the format.
+fn parse(from: Format, opts: &Opts, bytes: &[u8]) -> Result<Value, Error> {
+    match from {
+        Format::Csv | Format::Tsv => parser::CsvParser::new()
+            .delimiter(if from == Format::Tsv { '\t' } else { ',' })
+            .infer_types(opts.infer_types)
+            .parse_bytes(bytes),
+        Format::Env => parser::DotenvParser.parse_bytes(bytes),
+        Format::Ini => parser::IniParser.parse_bytes(bytes),
+        Format::Json => parser::JsonParser.parse_bytes(bytes),
+        Format::Json5 => parser::Json5Parser.parse_bytes(bytes),
+        Format::Ndjson => parser::NdjsonParser.parse_bytes(bytes),
+        Format::Properties => parser::PropertiesParser::new()
+            .expand_keys(opts.expand_keys)
+            .lenient(opts.lenient)
+            .parse_bytes(bytes),
+        Format::Toml => parser::TomlParser.parse_bytes(bytes),
+        Format::Xml => parser::XmlParser::new()
+            .always_array(opts.always_array)
+            .parse_bytes(bytes),
+        Format::Yaml => parser::YamlParser.parse_bytes(bytes),
+        Format::Line => parser::LineParser::new()
+            .root(&opts.root)
+            .delimiter(&opts.delimiter)
+            .equal(&opts.equal)
+            .bracket_paths(opts.bracket_paths)
+            .lenient(opts.lenient)
+            .parse_bytes(bytes),
+        Format::Msgpack => parser::MsgpackParser.parse_bytes(bytes),
+    }
+}
@@ -0,0 +1,91 @@
+//! Exit Codes, shared by the binaries.
+// Each binary uses only a part of them.
+#![allow(dead_code)]
+use df_utils::parser::CombinedError;
+use df_utils::{Generator, Value};
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process;
+
+/// The classes of failures in binaries, each of which has a distinct exit code.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Failure {
+    /// Invalid command line arguments.
+    Usage = 2,
+    /// Failed to read the input or to write the output.
+    Io = 3,
+    /// The input is invalid in its format.
+    Parse = 4,
+    /// The value can't be represented in the output format.
+    Generate = 5,
+}
+
+impl Failure {
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+
+    /// Exits with the code, assuming the error has already been reported.
+    pub fn exit(self) -> ! {
+        process::exit(self.code())
+    }
+
+    /// Prints the message to the standard error, and exits with the code.
+    pub fn fail<D: Display>(self, msg: D) -> ! {
+        if df_utils::io::color() {
+            eprintln!("\x1b[1;31merror\x1b[0m: {}", msg);
+        } else {
+            eprintln!("error: {}", msg);
+        }
+        self.exit()
+    }
+}
+
+/// Parses the command line arguments, exiting with `Failure::Usage` on errors.
+pub fn parse_args<C: clap::Clap>() -> C {
+    C::try_parse().unwrap_or_else(|e| {
+        if e.use_stderr() {
+            eprintln!("{}", e);
+            Failure::Usage.exit()
+        } else {
+            // Help or version messages
+            e.exit()
+        }
+    })
+}
+
+/// Exits on the error from reading or writing.
+pub fn io_error<T>(e: io::Error) -> T {
+    Failure::Io.fail(e)
+}
+
+/// Generates the output in the memory, exiting with `Failure::Generate` if
+/// the value can't be represented, so that it isn't mixed up with failures of
+/// writing.
+pub fn generate<F: FnOnce(&mut Vec<u8>) -> io::Result<()>>(generate: F) -> Vec<u8> {
+    let mut buf = Vec::new();
+    generate(&mut buf).unwrap_or_else(|e| Failure::Generate.fail(e));
+    buf
+}
+
+/// Generates the value by the generator, and writes it to `path`.
+pub fn write<G: Generator, P: AsRef<Path>>(generator: &G, path: Option<P>, value: &Value) {
+    let buf = generate(|buf| generator.generate(buf, value));
+    df_utils::io::Output::from_path(path)
+        .and_then(|mut output| output.write_all(&buf).and_then(|()| output.flush()))
+        .unwrap_or_else(io_error)
+}
+
+/// Exits on the error from reading `name` incrementally, where only the line
+/// of the error may be kept to be shown.
+pub fn stream_error<T>(name: &str, e: CombinedError) -> T {
+    match e {
+        CombinedError::Io(e) => io_error(e),
+        CombinedError::Parse(e) if e.snippet().is_some() => {
+            eprint!("{}", e.render("", name, df_utils::io::color()));
+            Failure::Parse.exit()
+        }
+        CombinedError::Parse(e) => Failure::Parse.fail(format_args!("{}: {}", name, e)),
+    }
+}
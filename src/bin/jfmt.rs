@@ -1,6 +1,9 @@
 //! JSON(JSON5) Formatter
+mod exit;
+
 use clap::Clap;
 use df_utils::*;
+use std::io::Write;
 use std::path::PathBuf;
 
 /// Options
@@ -28,26 +31,91 @@ struct Opts {
         conflicts_with = "minify"
     )]
     indent: usize,
+    #[clap(short = 's', long = "sort-keys", about = "Sort keys of objects.")]
+    sort_keys: bool,
+    #[clap(
+        short = 'c',
+        long = "canonical",
+        about = "Output canonical JSON (RFC 8785).",
+        conflicts_with_all = &["minify", "indent", "sort-keys"]
+    )]
+    canonical: bool,
+    #[clap(
+        short = 'n',
+        long = "ndjson",
+        about = "Read NDJSON, and format each record in turn.",
+        conflicts_with = "json5"
+    )]
+    ndjson: bool,
+    #[clap(
+        short = 'M',
+        long = "multi",
+        about = "Read multiple concatenated documents, and format each in turn.",
+        conflicts_with = "ndjson"
+    )]
+    multi: bool,
 }
 
-fn main() -> std::io::Result<()> {
-    let opts = Opts::parse();
+fn main() {
+    let opts: Opts = exit::parse_args();
+
+    if opts.ndjson || opts.multi {
+        let name = io::name(opts.input.as_ref());
+        let input = io::Input::from_path(opts.input.as_ref()).unwrap_or_else(exit::io_error);
+        let documents: Box<dyn Iterator<Item = _>> = if opts.ndjson {
+            Box::new(parser::NdjsonParser.records(input))
+        } else if opts.json5 {
+            Box::new(parser::Json5Parser.documents(input))
+        } else {
+            Box::new(parser::JsonParser.documents(input))
+        };
+
+        let output = io::Output::from_path(opts.output.as_ref()).unwrap_or_else(exit::io_error);
+        let mut output = std::io::BufWriter::new(output);
+        for document in documents {
+            let value = document.unwrap_or_else(|e| {
+                // Keep documents formatted so far.
+                let _ = output.flush();
+                exit::stream_error(&name, e)
+            });
+            let buf = exit::generate(|buf| generate(&opts, buf, &value));
+            output.write_all(&buf).unwrap_or_else(exit::io_error);
+            // Pretty JSON already ends with a line break.
+            if opts.canonical || opts.minify {
+                writeln!(output).unwrap_or_else(exit::io_error);
+            }
+        }
+        return output.flush().unwrap_or_else(exit::io_error);
+    }
 
+    let source = io::Source::from_path(opts.input.as_ref()).unwrap_or_else(exit::io_error);
     let value = if opts.json5 {
-        parser::Json5Parser.read_from(&mut io::Input::from_path(opts.input)?)
+        parser::Json5Parser.parse_bytes(&source.bytes)
     } else {
-        parser::JsonParser.read_from(&mut io::Input::from_path(opts.input)?)
+        parser::JsonParser.parse_bytes(&source.bytes)
     }
     .unwrap_or_else(|e| {
-        println!("{}", e);
-        std::process::exit(1);
+        source.report(&e);
+        exit::Failure::Parse.exit()
     });
 
-    if opts.minify {
-        generator::MinJsonGenerator.write_path(opts.output, &value)
+    let buf = exit::generate(|buf| generate(&opts, buf, &value));
+    io::Output::from_path(opts.output.as_ref())
+        .and_then(|mut output| output.write_all(&buf))
+        .unwrap_or_else(exit::io_error)
+}
+
+fn generate<W: Write>(opts: &Opts, buf: &mut W, value: &Value) -> std::io::Result<()> {
+    if opts.canonical {
+        generator::CanonicalJsonGenerator.generate(buf, value)
+    } else if opts.minify {
+        generator::MinJsonGenerator::new()
+            .sort_keys(opts.sort_keys)
+            .generate(buf, value)
     } else {
         generator::PrettyJsonGenerator::new()
             .indent(opts.indent)
-            .write_path(opts.output, &value)
+            .sort_keys(opts.sort_keys)
+            .generate(buf, value)
     }
 }
@@ -1,4 +1,6 @@
 //! JSON Generator
+mod exit;
+
 use clap::Clap;
 use df_utils::*;
 use std::path::PathBuf;
@@ -37,6 +39,18 @@ struct Opts {
         default_value = " = "
     )]
     equal: String,
+    #[clap(
+        short = 'b',
+        long = "bracket-paths",
+        about = "Use paths like `$.users[0][\"first.name\"]`."
+    )]
+    bracket_paths: bool,
+    #[clap(
+        short = 'l',
+        long = "lenient",
+        about = "Let later lines win over conflicting ones, filling gaps by null."
+    )]
+    lenient: bool,
     #[clap(short = 'm', long = "minify", about = "Minify JSON output.")]
     minify: bool,
     #[clap(
@@ -47,26 +61,40 @@ struct Opts {
         conflicts_with = "minify"
     )]
     indent: usize,
+    #[clap(
+        short = 'n',
+        long = "ndjson",
+        about = "Output each element of the root array as a line of NDJSON.",
+        conflicts_with_all = &["minify", "indent"]
+    )]
+    ndjson: bool,
 }
 
-fn main() -> std::io::Result<()> {
-    let opts = Opts::parse();
+fn main() {
+    let opts: Opts = exit::parse_args();
 
+    let source = io::Source::from_path(opts.input).unwrap_or_else(exit::io_error);
     let value = parser::LineParser::new()
         .root(opts.root)
         .delimiter(opts.delimiter)
         .equal(opts.equal)
-        .read_path(opts.input)
+        .bracket_paths(opts.bracket_paths)
+        .lenient(opts.lenient)
+        .parse_bytes(&source.bytes)
         .unwrap_or_else(|e| {
-            println!("{}", e);
-            std::process::exit(1);
+            source.report(&e);
+            exit::Failure::Parse.exit()
         });
 
-    if opts.minify {
-        generator::MinJsonGenerator.write_path(opts.output, &value)
+    if opts.ndjson {
+        exit::write(&generator::NdjsonGenerator::new(), opts.output, &value)
+    } else if opts.minify {
+        exit::write(&generator::MinJsonGenerator::new(), opts.output, &value)
     } else {
-        generator::PrettyJsonGenerator::new()
-            .indent(opts.indent)
-            .write_path(opts.output, &value)
+        exit::write(
+            generator::PrettyJsonGenerator::new().indent(opts.indent),
+            opts.output,
+            &value,
+        )
     }
 }
@@ -1,6 +1,10 @@
 //! JSON(JSON5) Parser
+mod exit;
+
 use clap::Clap;
+use df_utils::generator::Writer;
 use df_utils::*;
+use std::io::Write;
 use std::path::PathBuf;
 
 /// Options
@@ -37,26 +41,77 @@ struct Opts {
         default_value = " = "
     )]
     equal: String,
+    #[clap(
+        short = 'b',
+        long = "bracket-paths",
+        about = "Use paths like `$.users[0][\"first.name\"]`."
+    )]
+    bracket_paths: bool,
     #[clap(short = '5', long = "json5", about = "Enable JSON5 expanded syntax.")]
     json5: bool,
+    #[clap(short = 's', long = "sort-keys", about = "Sort keys of objects.")]
+    sort_keys: bool,
+    #[clap(
+        short = 'n',
+        long = "ndjson",
+        about = "Read NDJSON, prefixing paths with indices of records.",
+        conflicts_with = "json5"
+    )]
+    ndjson: bool,
 }
 
-fn main() -> std::io::Result<()> {
-    let opts = Opts::parse();
+fn main() {
+    let opts: Opts = exit::parse_args();
+
+    let mut generator = generator::LineGenerator::new();
+    generator
+        .root(opts.root)
+        .delimiter(opts.delimiter)
+        .equal(opts.equal)
+        .bracket_paths(opts.bracket_paths)
+        .sort_keys(opts.sort_keys);
 
+    // Sorting keys needs the whole document, otherwise lines are written while reading.
+    // Duplicate keys are then written as repeated lines, not merged by parsing.
+    if opts.ndjson || !opts.sort_keys {
+        let name = io::name(opts.input.as_ref());
+        let input = io::Input::from_path(opts.input).unwrap_or_else(exit::io_error);
+        let mut output = io::Output::from_path(opts.output).unwrap_or_else(exit::io_error);
+        let result = if opts.ndjson {
+            ndjson(&generator, input, &mut output)
+        } else {
+            generator.generate_stream(&mut output, input, opts.json5)
+        };
+        return result.unwrap_or_else(|e| exit::stream_error(&name, e));
+    }
+
+    let source = io::Source::from_path(opts.input).unwrap_or_else(exit::io_error);
     let value = if opts.json5 {
-        parser::Json5Parser.read_path(opts.input)
+        parser::Json5Parser.parse_bytes(&source.bytes)
     } else {
-        parser::JsonParser.read_path(opts.input)
+        parser::JsonParser.parse_bytes(&source.bytes)
     }
     .unwrap_or_else(|e| {
-        println!("{}", e);
-        std::process::exit(1);
+        source.report(&e);
+        exit::Failure::Parse.exit()
     });
 
-    generator::LineGenerator::new()
-        .root(opts.root)
-        .delimiter(opts.delimiter)
-        .equal(opts.equal)
-        .write_path(opts.output, &value)
+    exit::write(&generator, opts.output, &value)
+}
+
+/// Writes lines of each record, prefixing paths with its index.
+fn ndjson<W: Write>(
+    generator: &generator::LineGenerator,
+    input: io::Input,
+    output: W,
+) -> Result<(), parser::CombinedError> {
+    let mut output = std::io::BufWriter::new(output);
+    let mut writer = generator.writer(&mut output);
+    writer.begin_array()?;
+    for record in parser::NdjsonParser.records(input) {
+        writer.value(&record?)?;
+    }
+    writer.end_array()?;
+    writer.finish()?;
+    Ok(())
 }
@@ -0,0 +1,35 @@
+//! TOML Formatter
+mod exit;
+
+use clap::Clap;
+use df_utils::*;
+use std::path::PathBuf;
+
+/// Options
+#[derive(Clone, Debug, Clap)]
+#[clap(
+    name = "tfmt",
+    version = clap::crate_version!(),
+    author = clap::crate_authors!(),
+    about = "Simple CLI TOML Formatter"
+)]
+struct Opts {
+    #[clap(name = "INPUT", about = "The input TOML file.")]
+    input: Option<PathBuf>,
+    #[clap(short = 'o', long = "output", about = "The output file.")]
+    output: Option<PathBuf>,
+}
+
+fn main() {
+    let opts: Opts = exit::parse_args();
+
+    let source = io::Source::from_path(opts.input).unwrap_or_else(exit::io_error);
+    let value = parser::TomlParser
+        .parse_bytes(&source.bytes)
+        .unwrap_or_else(|e| {
+            source.report(&e);
+            exit::Failure::Parse.exit()
+        });
+
+    exit::write(&generator::TomlGenerator, opts.output, &value)
+}
@@ -0,0 +1,73 @@
+//! TOML Generator
+mod exit;
+
+use clap::Clap;
+use df_utils::*;
+use std::path::PathBuf;
+
+/// Options
+#[derive(Clone, Debug, Clap)]
+#[clap(
+    name = "tgen",
+    version = clap::crate_version!(),
+    author = clap::crate_authors!(),
+    about = "Simple CLI TOML Generator"
+)]
+struct Opts {
+    #[clap(name = "INPUT", about = "The input file.")]
+    input: Option<PathBuf>,
+    #[clap(short = 'o', long = "output", about = "The output TOML file.")]
+    output: Option<PathBuf>,
+    #[clap(
+        short = 'r',
+        long = "root",
+        about = "The root indicator.",
+        default_value = "$"
+    )]
+    root: String,
+    #[clap(
+        short = 'd',
+        long = "delimiter",
+        about = "The delimiter for the path.",
+        default_value = "."
+    )]
+    delimiter: String,
+    #[clap(
+        short = 'e',
+        long = "equal",
+        about = "The equal symbol.",
+        default_value = " = "
+    )]
+    equal: String,
+    #[clap(
+        short = 'b',
+        long = "bracket-paths",
+        about = "Use paths like `$.users[0][\"first.name\"]`."
+    )]
+    bracket_paths: bool,
+    #[clap(
+        short = 'l',
+        long = "lenient",
+        about = "Let later lines win over conflicting ones, filling gaps by null."
+    )]
+    lenient: bool,
+}
+
+fn main() {
+    let opts: Opts = exit::parse_args();
+
+    let source = io::Source::from_path(opts.input).unwrap_or_else(exit::io_error);
+    let value = parser::LineParser::new()
+        .root(opts.root)
+        .delimiter(opts.delimiter)
+        .equal(opts.equal)
+        .bracket_paths(opts.bracket_paths)
+        .lenient(opts.lenient)
+        .parse_bytes(&source.bytes)
+        .unwrap_or_else(|e| {
+            source.report(&e);
+            exit::Failure::Parse.exit()
+        });
+
+    exit::write(&generator::TomlGenerator, opts.output, &value)
+}
@@ -0,0 +1,68 @@
+//! TOML Parser
+mod exit;
+
+use clap::Clap;
+use df_utils::*;
+use std::path::PathBuf;
+
+/// Options
+#[derive(Clone, Debug, Clap)]
+#[clap(
+    name = "tprs",
+    version = clap::crate_version!(),
+    author = clap::crate_authors!(),
+    about = "Simple CLI TOML Parser"
+)]
+struct Opts {
+    #[clap(name = "INPUT", about = "The input TOML file.")]
+    input: Option<PathBuf>,
+    #[clap(short = 'o', long = "output", about = "The output file.")]
+    output: Option<PathBuf>,
+    #[clap(
+        short = 'r',
+        long = "root",
+        about = "The root indicator.",
+        default_value = "$"
+    )]
+    root: String,
+    #[clap(
+        short = 'd',
+        long = "delimiter",
+        about = "The delimiter for the path.",
+        default_value = "."
+    )]
+    delimiter: String,
+    #[clap(
+        short = 'e',
+        long = "equal",
+        about = "The equal symbol.",
+        default_value = " = "
+    )]
+    equal: String,
+    #[clap(
+        short = 'b',
+        long = "bracket-paths",
+        about = "Use paths like `$.users[0][\"first.name\"]`."
+    )]
+    bracket_paths: bool,
+}
+
+fn main() {
+    let opts: Opts = exit::parse_args();
+
+    let source = io::Source::from_path(opts.input).unwrap_or_else(exit::io_error);
+    let value = parser::TomlParser
+        .parse_bytes(&source.bytes)
+        .unwrap_or_else(|e| {
+            source.report(&e);
+            exit::Failure::Parse.exit()
+        });
+
+    let mut generator = generator::LineGenerator::new();
+    generator
+        .root(opts.root)
+        .delimiter(opts.delimiter)
+        .equal(opts.equal)
+        .bracket_paths(opts.bracket_paths);
+    exit::write(&generator, opts.output, &value)
+}
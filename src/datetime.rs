@@ -1,3 +1,4 @@
+use std::convert::TryFrom;
 use std::fmt;
 
 /// A struct represents date/time in TOML
@@ -20,6 +21,62 @@ impl DateTime {
             Ok(DateTime { date, time, offset })
         }
     }
+
+    /// Makes a date-time in UTC from seconds and nanoseconds since the Unix
+    /// epoch.
+    pub(crate) fn from_timestamp(seconds: i64, nanosecond: u32) -> Result<Self, ()> {
+        let (year, month, day) = civil_from_days(seconds.div_euclid(86400));
+        let second = seconds.rem_euclid(86400);
+        let date = Date::new(u16::try_from(year).or(Err(()))?, month, day)?;
+        let time = Time::new(
+            (second / 3600) as u8,
+            (second / 60 % 60) as u8,
+            (second % 60) as u8,
+            nanosecond,
+        )?;
+        Self::new(Some(date), Some(time), Some(Offset::new(0, 0)?))
+    }
+
+    /// Seconds and nanoseconds since the Unix epoch, if the date, the time and
+    /// the offset are all specified. Leap seconds are counted as the next ones.
+    pub(crate) fn timestamp(&self) -> Option<(i64, u32)> {
+        let (date, time, offset) = (self.date?, self.time?, self.offset?);
+        let days = days_from_civil(date.year.into(), date.month.into(), date.day.into());
+        let minutes = i64::from(offset.hour) * 60
+            + i64::from(offset.minute) * if offset.hour < 0 { -1 } else { 1 };
+        let seconds = days * 86400
+            + i64::from(time.hour) * 3600
+            + i64::from(time.minute) * 60
+            + i64::from(time.second)
+            - minutes * 60;
+        Some((seconds, time.nanosecond))
+    }
+}
+
+/// Counts days since 1970-01-01 in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    // Years starting from March, to put leap days at the end.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// The inverse of `days_from_civil`.
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month + 2) / 5 + 1) as u8;
+    let month = if month < 10 { month + 3 } else { month - 9 } as u8;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
 }
 
 impl fmt::Display for DateTime {
@@ -91,7 +148,7 @@ pub(crate) struct Time {
 impl Time {
     pub(crate) fn new(hour: u8, minute: u8, second: u8, nanosecond: u32) -> Result<Self, ()> {
         // Consider leap seconds
-        if hour <= 24 && minute <= 59 && second <= 60 && nanosecond < 10u32.pow(10) {
+        if hour < 24 && minute <= 59 && second <= 60 && nanosecond < 10u32.pow(9) {
             Ok(Time {
                 hour,
                 minute,
@@ -138,7 +195,7 @@ impl fmt::Display for Offset {
 
 impl Offset {
     pub(crate) fn new(hour: i8, minute: u8) -> Result<Self, ()> {
-        if (-12 <= hour && hour <= 13 && minute < 60) || (hour == 14 && minute == 0) {
+        if ((-12..=13).contains(&hour) && minute < 60) || (hour == 14 && minute == 0) {
             Ok(Offset { hour, minute })
         } else {
             Err(())
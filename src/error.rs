@@ -0,0 +1,288 @@
+//! Error Type
+use peg::error::ParseError;
+use peg::str::LineCol;
+use std::fmt;
+
+/// The error type for parsers, which points to the location of the error.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+    format: &'static str,
+    line: usize,
+    column: usize,
+    offset: usize,
+    expected: Vec<&'static str>,
+    /// The line and the column of an earlier part conflicting with this one.
+    previous: Option<(usize, usize)>,
+    /// The line of the input, kept by parsers which don't keep the input.
+    snippet: Option<String>,
+    /// Whether the input is binary, which is pointed by the byte offset.
+    binary: bool,
+}
+
+impl Error {
+    /// Makes an error at `offset` of `input`.
+    pub(crate) fn new(
+        format: &'static str,
+        input: &str,
+        offset: usize,
+        expected: &[&'static str],
+    ) -> Self {
+        let (line, column) = location(input, offset);
+        Self::with_location(format, line, column, offset, expected.iter().copied())
+    }
+
+    /// Points also to `offset` of `input`, which the error conflicts with.
+    pub(crate) fn conflicting(self, input: &str, offset: usize) -> Self {
+        let (line, column) = location(input, offset);
+        self.conflicting_at(line, column)
+    }
+
+    pub(crate) fn conflicting_at(mut self, line: usize, column: usize) -> Self {
+        self.previous = Some((line, column));
+        self
+    }
+
+    /// Keeps the line of the error, to be rendered without the input.
+    pub(crate) fn with_snippet(mut self, line: String) -> Self {
+        self.snippet = Some(line);
+        self
+    }
+
+    /// Makes an error at `offset` of binary input, which is regarded as a line
+    /// of bytes, and shown by the offset.
+    pub(crate) fn binary(format: &'static str, offset: usize, expected: &[&'static str]) -> Self {
+        Self {
+            binary: true,
+            ..Self::with_location(format, 1, offset + 1, offset, expected.iter().copied())
+        }
+    }
+
+    pub(crate) fn from_peg(format: &'static str, e: ParseError<LineCol>) -> Self {
+        Self::with_location(
+            format,
+            e.location.line,
+            e.location.column,
+            e.location.offset,
+            e.expected.tokens(),
+        )
+    }
+
+    pub(crate) fn with_location<I: Iterator<Item = &'static str>>(
+        format: &'static str,
+        line: usize,
+        column: usize,
+        offset: usize,
+        expected: I,
+    ) -> Self {
+        let mut expected = expected.collect::<Vec<_>>();
+        expected.sort_unstable();
+        expected.dedup();
+        Self {
+            format,
+            line,
+            column,
+            offset,
+            expected,
+            previous: None,
+            snippet: None,
+            binary: false,
+        }
+    }
+
+    /// Reports the error as of `format`, for formats made of another one.
+    pub(crate) fn in_format(mut self, format: &'static str) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Moves the location, for an error in a part of the input starting at the
+    /// head of the line `line` and the byte `offset`.
+    pub(crate) fn within(mut self, line: usize, offset: usize) -> Self {
+        self.line += line - 1;
+        self.offset += offset;
+        self
+    }
+
+    /// The name of the format being parsed.
+    pub fn format(&self) -> &'static str {
+        self.format
+    }
+
+    /// The line number, starting from 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The column number in characters, starting from 1.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// The byte offset from the start of the input.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The sorted set of tokens expected at the location.
+    pub fn expected(&self) -> &[&'static str] {
+        &self.expected
+    }
+
+    /// The line and the column of an earlier part of the input, which this
+    /// conflicts with, like the first one of duplicate paths.
+    pub fn previous(&self) -> Option<(usize, usize)> {
+        self.previous
+    }
+
+    /// The line of the error, kept by parsers reading the input incrementally
+    /// unless it is too long.
+    pub fn snippet(&self) -> Option<&str> {
+        self.snippet.as_deref()
+    }
+
+    /// Renders a compiler-style diagnostic, showing the line of `source` with a
+    /// caret under the column. `name` is the file name to show, and `color`
+    /// enables ANSI colors. The kept line is shown instead if any, where
+    /// `source` may be empty. Binary input isn't shown, but the byte offset.
+    pub fn render(&self, source: &str, name: &str, color: bool) -> String {
+        let (red, blue, reset) = if color {
+            ("\x1b[1;31m", "\x1b[1;34m", "\x1b[0m")
+        } else {
+            ("", "", "")
+        };
+        if self.binary {
+            return format!(
+                "{red}error{reset}: invalid {format}\n \
+                 {blue}-->{reset} {name}, byte {offset}\n  \
+                 {blue}={reset} {red}{message}{reset}\n",
+                red = red,
+                blue = blue,
+                reset = reset,
+                format = self.format,
+                name = name,
+                offset = self.offset,
+                message = self.message(),
+            );
+        }
+
+        let line = match &self.snippet {
+            Some(line) => line,
+            None => source.lines().nth(self.line - 1).unwrap_or(""),
+        }
+        .trim_end_matches('\r');
+        // Keep tabs to put the caret at the same position.
+        let padding = line
+            .chars()
+            .take(self.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+        let number = self.line.to_string();
+        let gutter = " ".repeat(number.len());
+        let note = match self.previous {
+            Some((line, column)) => format!(
+                "{gutter} {blue}={reset} note: conflicting with {name}:{line}:{column}\n",
+                gutter = gutter,
+                blue = blue,
+                reset = reset,
+                name = name,
+                line = line,
+                column = column,
+            ),
+            None => String::new(),
+        };
+
+        format!(
+            "{red}error{reset}: invalid {format}\n\
+             {gutter}{blue}-->{reset} {name}:{line}:{column}\n\
+             {gutter} {blue}|{reset}\n\
+             {blue}{number} |{reset} {source}\n\
+             {gutter} {blue}|{reset} {padding}{red}^ {message}{reset}\n\
+             {note}",
+            red = red,
+            blue = blue,
+            reset = reset,
+            format = self.format,
+            gutter = gutter,
+            name = name,
+            line = self.line,
+            column = self.column,
+            number = number,
+            source = line,
+            padding = padding,
+            message = self.message(),
+            note = note,
+        )
+    }
+
+    /// Describes expected tokens in a human-readable way.
+    fn message(&self) -> String {
+        let mut tokens = Vec::new();
+        for token in self.expected.iter().map(|t| describe(t)) {
+            if !tokens.contains(&token) {
+                tokens.push(token);
+            }
+        }
+        match tokens.split_last() {
+            None => "unexpected input".to_string(),
+            Some((last, [])) => format!("expected {}", last),
+            Some((last, init)) => format!("expected {} or {}", init.join(", "), last),
+        }
+    }
+}
+
+/// The line and the column in characters at `offset` of `input`.
+fn location(input: &str, offset: usize) -> (usize, usize) {
+    let before = &input[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before.chars().rev().take_while(|&c| c != '\n').count() + 1;
+    (line, column)
+}
+
+/// Converts a token reported by `peg` into words.
+fn describe(token: &str) -> String {
+    if token.len() >= 2 && token.starts_with('"') && token.ends_with('"') {
+        let literal = &token[1..token.len() - 1];
+        format!("`{}`", literal.replace("\\\"", "\"").replace("\\\\", "\\"))
+    } else if token == "EOF" {
+        "end of input".to_string()
+    } else if token.starts_with('[') && token.contains("'0'..='9'") {
+        "a digit".to_string()
+    } else if token.starts_with('[') && token.contains("' '") {
+        "whitespace".to_string()
+    } else if token.starts_with('[') {
+        "a character".to_string()
+    } else {
+        token.to_string()
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.binary {
+            write!(f, "invalid {} at byte {}: ", self.format, self.offset)?;
+        } else {
+            write!(
+                f,
+                "invalid {} at {}:{}: ",
+                self.format, self.line, self.column
+            )?;
+        }
+        match self.expected.as_slice() {
+            [] => write!(f, "unexpected input"),
+            [e] => write!(f, "expected {}", e),
+            [first, rest @ ..] => {
+                write!(f, "expected one of {}", first)?;
+                for e in rest {
+                    write!(f, ", {}", e)?;
+                }
+                Ok(())
+            }
+        }?;
+        match self.previous {
+            Some((line, column)) => write!(f, ", conflicting with {}:{}", line, column),
+            None => Ok(()),
+        }
+    }
+}
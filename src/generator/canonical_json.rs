@@ -0,0 +1,106 @@
+/// Canonical JSON Generator, following RFC 8785 (JSON Canonicalization Scheme).
+///
+/// Keys are sorted by UTF-16 code units and numbers are serialized as ECMAScript
+/// does, so integers beyond the precision of `f64` are rounded. `NaN` and
+/// infinities can't be represented and result in an error.
+pub struct CanonicalJsonGenerator;
+
+use super::invalid;
+use super::min_json::string;
+use crate::{Generator, Value};
+use std::io::{self, Write};
+
+impl Generator for CanonicalJsonGenerator {
+    fn generate<W: Write>(&self, buf: &mut W, value: &Value) -> io::Result<()> {
+        // Generate into the memory first, not to leave a broken output on errors.
+        let mut out = Vec::new();
+        inner_generate(&mut out, value)?;
+        buf.write_all(&out)
+    }
+}
+
+fn inner_generate<W: Write>(buf: &mut W, value: &Value) -> io::Result<()> {
+    match value {
+        Value::Null => write!(buf, "null")?,
+        Value::Boolean(b) => write!(buf, "{}", b)?,
+        Value::Int(i) => write!(buf, "{}", number(*i as f64)?)?,
+        Value::Float(f) => write!(buf, "{}", number(*f)?)?,
+        Value::String(s) => string(buf, s)?,
+        Value::DateTime(d) => string(buf, &d.to_string())?,
+        Value::Array(vs) => {
+            write!(buf, "[")?;
+            for (i, v) in vs.iter().enumerate() {
+                if i != 0 {
+                    write!(buf, ",")?;
+                }
+                inner_generate(buf, v)?;
+            }
+            write!(buf, "]")?;
+        }
+        Value::Map(m) => {
+            let mut entries = m.iter().collect::<Vec<_>>();
+            entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+
+            write!(buf, "{{")?;
+            for (i, (k, v)) in entries.into_iter().enumerate() {
+                if i != 0 {
+                    write!(buf, ",")?;
+                }
+                string(buf, k)?;
+                write!(buf, ":")?;
+                inner_generate(buf, v)?;
+            }
+            write!(buf, "}}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Serializes the number as `Number.prototype.toString` of ECMAScript.
+fn number(f: f64) -> io::Result<String> {
+    if !f.is_finite() {
+        return Err(invalid(
+            "NaN and Infinity can't be represented in canonical JSON",
+        ));
+    }
+    if f == 0.0 {
+        // Including the negative zero.
+        return Ok("0".to_string());
+    }
+
+    // The shortest digits which round trip, like "1.2345e6".
+    let mut sci = format!("{:e}", f.abs());
+    // Of the digits as short, ECMAScript takes the closest ones, choosing the
+    // even ones on ties, where the shortest ones of Rust may round up.
+    let precise = format!("{:.*e}", sci.find('e').unwrap().saturating_sub(2), f.abs());
+    if precise.parse() == Ok(f.abs()) {
+        sci = precise;
+    }
+    let (mantissa, exp) = sci.split_at(sci.find('e').unwrap());
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    let n = exp[1..].parse::<i32>().unwrap() + 1;
+
+    let abs = if k <= n && n <= 21 {
+        [digits, "0".repeat((n - k) as usize)].concat()
+    } else if 0 < n && n <= 21 {
+        let (int, frac) = digits.split_at(n as usize);
+        [int, ".", frac].concat()
+    } else if -6 < n && n <= 0 {
+        ["0.", &"0".repeat(-n as usize), &digits].concat()
+    } else {
+        let (first, rest) = digits.split_at(1);
+        let exp = if n > 0 {
+            format!("e+{}", n - 1)
+        } else {
+            format!("e-{}", 1 - n)
+        };
+        if rest.is_empty() {
+            [first, &exp].concat()
+        } else {
+            [first, ".", rest, &exp].concat()
+        }
+    };
+
+    Ok(if f < 0.0 { ["-", &abs].concat() } else { abs })
+}
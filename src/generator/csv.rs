@@ -0,0 +1,129 @@
+//! CSV Generator
+use super::{flatten, invalid, text};
+use crate::{Generator, Map, Value};
+use indexmap::IndexSet;
+use std::io::{self, Write};
+
+/// CSV (RFC 4180) Generator, which writes each element of the root array as a
+/// record.
+///
+/// Records must be maps, and the union of their keys makes the header. Keys
+/// are column names as they are, while nested values are flattened with paths
+/// like the line format, e.g. `user.name` and `tags.0`, which must not collide
+/// with the keys. Empty arrays and maps are written as `[]` and `{}`. Fields
+/// containing the delimiter, `"` or line breaks are double quoted.
+#[derive(Clone, Debug)]
+pub struct CsvGenerator {
+    delimiter: char,
+    crlf: bool,
+}
+
+impl Default for CsvGenerator {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            crlf: true,
+        }
+    }
+}
+
+impl CsvGenerator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the delimiter of fields, like `'\t'` for TSV.
+    pub fn delimiter(&mut self, delimiter: char) -> &mut Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Ends records by CRLF as RFC 4180, or by LF if `false`.
+    pub fn crlf(&mut self, crlf: bool) -> &mut Self {
+        self.crlf = crlf;
+        self
+    }
+}
+
+impl Generator for CsvGenerator {
+    fn generate<W: Write>(&self, buf: &mut W, value: &Value) -> io::Result<()> {
+        let records = match value {
+            Value::Array(vs) => vs,
+            _ => return Err(invalid("The root of CSV must be an array")),
+        };
+        let mut rows = Vec::new();
+        for record in records {
+            match record {
+                Value::Map(m) => rows.push(row(m)?),
+                _ => return Err(invalid("Records of CSV must be maps")),
+            }
+        }
+
+        let columns = rows.iter().flat_map(Map::keys).collect::<IndexSet<_>>();
+        if columns.is_empty() {
+            return Ok(());
+        }
+        // Not to write a part of the output on errors.
+        let mut out = Vec::new();
+        let header = columns.iter().map(|k| self.field(k, columns.len()));
+        self.write_row(&mut out, header)?;
+        for row in &rows {
+            let fields = columns.iter().map(|k| match row.get(*k) {
+                Some(v) => self.field(&text(v), columns.len()),
+                None => self.field("", columns.len()),
+            });
+            self.write_row(&mut out, fields)?;
+        }
+        buf.write_all(&out)?;
+        buf.flush()
+    }
+}
+
+/// Makes the columns of the record, keeping keys of scalars as they are.
+fn row(record: &Map) -> io::Result<Map> {
+    let mut row = Map::new();
+    for (k, v) in record {
+        let nested = match v {
+            Value::Array(vs) => !vs.is_empty(),
+            Value::Map(m) => !m.is_empty(),
+            _ => false,
+        };
+        let mut flat = Map::new();
+        if nested {
+            let key = crate::line::key(k, ".", " = ");
+            flatten(&mut flat, Some(&key), v, Some(" = "));
+        } else {
+            flat.insert(k.clone(), v.clone());
+        }
+        for (column, v) in flat {
+            if row.contains_key(&column) {
+                return Err(invalid("Columns of CSV must be unique after flattening"));
+            }
+            row.insert(column, v);
+        }
+    }
+    Ok(row)
+}
+
+impl CsvGenerator {
+    fn write_row<W: Write, I: Iterator<Item = String>>(
+        &self,
+        buf: &mut W,
+        fields: I,
+    ) -> io::Result<()> {
+        let mut delimiter = [0; 4];
+        let delimiter = self.delimiter.encode_utf8(&mut delimiter);
+        let row = fields.collect::<Vec<_>>().join(delimiter);
+        write!(buf, "{}{}", row, if self.crlf { "\r\n" } else { "\n" })
+    }
+
+    /// Quotes the field if needed, where a single empty field is quoted not to
+    /// be a blank line.
+    fn field(&self, s: &str, columns: usize) -> String {
+        if s.contains(&[self.delimiter, '"', '\r', '\n'][..]) || s.is_empty() && columns == 1 {
+            format!("\"{}\"", s.replace('"', "\"\""))
+        } else {
+            s.to_string()
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Dotenv Generator
+use super::{invalid, text};
+use crate::{Generator, Value};
+use std::io::{self, Write};
+
+/// Dotenv (`.env`) Generator, which writes entries of the root map as lines like
+/// `KEY=value`.
+///
+/// Values must be scalars. Values with characters special to shells are single
+/// quoted, or double quoted with escapes if they have `'` or control characters.
+#[derive(Clone, Debug, Default)]
+pub struct DotenvGenerator {
+    export: bool,
+}
+
+impl DotenvGenerator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prefixes lines by `export`, to be sourced by shells.
+    pub fn export(&mut self, export: bool) -> &mut Self {
+        self.export = export;
+        self
+    }
+}
+
+impl Generator for DotenvGenerator {
+    fn generate<W: Write>(&self, buf: &mut W, value: &Value) -> io::Result<()> {
+        let map = match value {
+            Value::Map(m) => m,
+            _ => return Err(invalid("The root of dotenv must be a map")),
+        };
+        // Not to write a part of the output on errors.
+        let mut out = Vec::new();
+        for (k, v) in map {
+            if !is_name(k) {
+                return Err(invalid("Keys of dotenv must be names like `APP_PORT`"));
+            }
+            if matches!(v, Value::Array(_) | Value::Map(_)) {
+                return Err(invalid("Values of dotenv must be scalars"));
+            }
+            let export = if self.export { "export " } else { "" };
+            writeln!(out, "{}{}={}", export, k, quote(&text(v)))?;
+        }
+        buf.write_all(&out)
+    }
+}
+
+/// Whether the key can be read as a name, which doesn't start with digits, `.`
+/// or `-`.
+fn is_name(key: &str) -> bool {
+    let name = |c: char| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-');
+    key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') && key.chars().all(name)
+}
+
+/// Quotes the value if it has characters other than ones safe in shells.
+fn quote(s: &str) -> String {
+    let safe = |c: char| c.is_alphanumeric() || "_-.,/:@%+=".contains(c);
+    if s.chars().all(safe) {
+        s.to_string()
+    } else if !s.chars().any(|c| c == '\'' || c.is_control()) {
+        format!("'{}'", s)
+    } else {
+        let mut quoted = String::from("\"");
+        for c in s.chars() {
+            match c {
+                '\n' => quoted.push_str("\\n"),
+                '\r' => quoted.push_str("\\r"),
+                '\t' => quoted.push_str("\\t"),
+                '\\' | '"' | '$' | '`' => quoted.extend(&['\\', c]),
+                c => quoted.push(c),
+            }
+        }
+        quoted.push('"');
+        quoted
+    }
+}
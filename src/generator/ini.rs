@@ -0,0 +1,74 @@
+//! INI Generator
+use super::{invalid, text};
+use crate::{line, Generator, Value};
+use std::io::{self, Write};
+
+/// INI Generator, which writes maps in the root map as sections.
+///
+/// Scalars in the root map are written first, before any section. Arrays and
+/// maps nested in sections can't be written. Names, keys and values which
+/// can't be read back as they are are double quoted.
+#[derive(Copy, Clone, Debug)]
+pub struct IniGenerator;
+
+impl Generator for IniGenerator {
+    fn generate<W: Write>(&self, buf: &mut W, value: &Value) -> io::Result<()> {
+        let root = match value {
+            Value::Map(m) => m,
+            _ => return Err(invalid("The root of INI must be a map")),
+        };
+        // Not to write a part of the output on errors.
+        let mut out = Vec::new();
+        write_entries(
+            &mut out,
+            root.iter().filter(|(_, v)| !matches!(v, Value::Map(_))),
+        )?;
+        let mut first = root.values().all(|v| matches!(v, Value::Map(_)));
+        for (name, v) in root {
+            if let Value::Map(m) = v {
+                if !first {
+                    writeln!(out)?;
+                }
+                first = false;
+                writeln!(out, "[{}]", quote(name, &[']']))?;
+                write_entries(&mut out, m.iter())?;
+            }
+        }
+        buf.write_all(&out)
+    }
+}
+
+fn write_entries<'a, W: Write, I: Iterator<Item = (&'a String, &'a Value)>>(
+    buf: &mut W,
+    entries: I,
+) -> io::Result<()> {
+    for (k, v) in entries {
+        let value = match v {
+            Value::Array(_) => return Err(invalid("INI can't have arrays")),
+            Value::Map(_) => return Err(invalid("INI can't nest maps in sections")),
+            v => text(v),
+        };
+        let key = quote(k, &['=', ':']);
+        if value.is_empty() {
+            writeln!(buf, "{} =", key)?;
+        } else {
+            writeln!(buf, "{} = {}", key, quote(&value, &[]))?;
+        }
+    }
+    Ok(())
+}
+
+/// Quotes the text if it has any of `ends` or it would be trimmed or read as a
+/// comment, a section or a quoted string.
+fn quote(s: &str, ends: &[char]) -> String {
+    let plain = !s.is_empty()
+        && !s.starts_with(&['"', ';', '#', '['][..])
+        && s.trim() == s
+        && !s.contains(ends)
+        && !s.chars().any(char::is_control);
+    if plain {
+        s.to_string()
+    } else {
+        line::string(s)
+    }
+}
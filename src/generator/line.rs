@@ -1,13 +1,17 @@
 //! Line Generator
 
-use crate::{Generator, Value};
-use std::io;
+use super::writer::{self, Container, Nesting, Slot, Writer};
+use crate::parser::{CombinedError, Event, Events};
+use crate::{line, Generator, Map, Value};
+use std::io::{self, Write};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct LineGenerator {
     root: String,
     delimiter: String,
     equal: String,
+    sort_keys: bool,
+    bracket_paths: bool,
 }
 
 impl Default for LineGenerator {
@@ -16,6 +20,8 @@ impl Default for LineGenerator {
             root: "$".to_string(),
             delimiter: ".".to_string(),
             equal: " = ".to_string(),
+            sort_keys: false,
+            bracket_paths: false,
         }
     }
 }
@@ -39,44 +45,182 @@ impl LineGenerator {
         self.equal = equal.into();
         self
     }
+
+    pub fn sort_keys(&mut self, sort_keys: bool) -> &mut Self {
+        self.sort_keys = sort_keys;
+        self
+    }
+
+    /// Writes paths like `$.users[0]["first.name"]`.
+    pub fn bracket_paths(&mut self, bracket_paths: bool) -> &mut Self {
+        self.bracket_paths = bracket_paths;
+        self
+    }
+
+    /// Makes a writer to write lines incrementally. Keys are never sorted,
+    /// except in maps written as a whole by `Writer::value`.
+    pub fn writer<W: Write>(&self, buf: W) -> LineWriter<'_, W> {
+        LineWriter {
+            buf,
+            config: self,
+            nesting: Nesting::default(),
+            locs: Vec::new(),
+            key: None,
+        }
+    }
+
+    /// Generates lines while reading JSON (or JSON5 if `json5`) from `input`,
+    /// keeping only the current path in the memory. Keys are never sorted, and
+    /// duplicate keys are written as repeated lines instead of the last value.
+    pub fn generate_stream<W: Write, R: io::Read>(
+        &self,
+        buf: &mut W,
+        input: R,
+        json5: bool,
+    ) -> Result<(), CombinedError> {
+        let mut buf = io::BufWriter::new(buf);
+        let mut writer = self.writer(&mut buf);
+        for event in Events::new(input, json5) {
+            match event? {
+                Event::StartArray => writer.begin_array()?,
+                Event::EndArray => writer.end_array()?,
+                Event::StartMap => writer.begin_object()?,
+                Event::Key(k) => writer.key(&k)?,
+                Event::EndMap => writer.end_object()?,
+                Event::Value(v) => writer.value(&v)?,
+            }
+        }
+        writer.finish()?;
+        Ok(())
+    }
+
+    fn index(&self, loc: &str, i: usize) -> String {
+        if self.bracket_paths {
+            format!("{}[{}]", loc, i)
+        } else {
+            [loc, &self.delimiter, &i.to_string()].concat()
+        }
+    }
+
+    fn key(&self, loc: &str, k: &str) -> String {
+        if self.bracket_paths {
+            [loc, &line::bracket_key(k, &self.delimiter, &self.equal)].concat()
+        } else {
+            [
+                loc,
+                &self.delimiter,
+                &line::key(k, &self.delimiter, &self.equal),
+            ]
+            .concat()
+        }
+    }
 }
 
 impl Generator for LineGenerator {
     fn generate<W: io::Write>(&self, buf: &mut W, value: &Value) -> io::Result<()> {
-        inner(buf, value, self, &self.root)
+        let mut writer = self.writer(buf);
+        writer.value(value)?;
+        writer.finish()
     }
 }
 
-fn inner<W: io::Write>(
+/// The writer made by `LineGenerator::writer`.
+#[derive(Debug)]
+pub struct LineWriter<'a, W> {
+    buf: W,
+    config: &'a LineGenerator,
+    nesting: Nesting,
+    /// Locations of containers being written.
+    locs: Vec<String>,
+    /// The location of the value following the last key.
+    key: Option<String>,
+}
+
+impl<W: Write> LineWriter<'_, W> {
+    /// Checks a value can be written now, and returns its location.
+    fn loc(&mut self) -> io::Result<String> {
+        Ok(match self.nesting.value()? {
+            Slot::Root => self.config.root.clone(),
+            Slot::Element(i) => self.config.index(self.locs.last().unwrap(), i),
+            Slot::Entry => self.key.take().unwrap(),
+        })
+    }
+
+    fn begin(&mut self, container: Container) -> io::Result<()> {
+        let loc = self.loc()?;
+        self.nesting.begin(container);
+        self.locs.push(loc);
+        Ok(())
+    }
+
+    /// Ends the container, writing a line if it is empty.
+    fn end(&mut self, container: Container, empty: Value) -> io::Result<()> {
+        let len = self.nesting.end(container)?;
+        let loc = self.locs.pop().unwrap();
+        if len == 0 {
+            write_line(&mut self.buf, &empty, self.config, &loc)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Writer for LineWriter<'_, W> {
+    fn begin_array(&mut self) -> io::Result<()> {
+        self.begin(Container::Array)
+    }
+
+    fn end_array(&mut self) -> io::Result<()> {
+        self.end(Container::Array, Value::Array(Vec::new()))
+    }
+
+    fn begin_object(&mut self) -> io::Result<()> {
+        self.begin(Container::Object)
+    }
+
+    fn key(&mut self, key: &str) -> io::Result<()> {
+        self.nesting.key()?;
+        self.key = Some(self.config.key(self.locs.last().unwrap(), key));
+        Ok(())
+    }
+
+    fn end_object(&mut self) -> io::Result<()> {
+        self.end(Container::Object, Value::Map(Map::new()))
+    }
+
+    fn value(&mut self, value: &Value) -> io::Result<()> {
+        match value {
+            Value::Array(_) | Value::Map(_) => {
+                writer::container(self, value, self.config.sort_keys)
+            }
+            _ => {
+                let loc = self.loc()?;
+                write_line(&mut self.buf, value, self.config, &loc)
+            }
+        }
+    }
+
+    fn finish(mut self) -> io::Result<()> {
+        self.nesting.finish()?;
+        self.buf.flush()
+    }
+}
+
+/// Writes a line of a scalar or an empty container.
+fn write_line<W: io::Write>(
     buf: &mut W,
     value: &Value,
     config: &LineGenerator,
     loc: &str,
 ) -> io::Result<()> {
     match value {
-        Value::Null => writeln!(buf, "{}{}null", loc, config.equal)?,
-        Value::Boolean(b) => writeln!(buf, "{}{}{}", loc, config.equal, b)?,
-        Value::Int(i) => writeln!(buf, "{}{}{}", loc, config.equal, i)?,
-        Value::Float(f) => writeln!(buf, "{}{}{}", loc, config.equal, f)?,
-        Value::String(s) => writeln!(buf, "{}{}{:?}", loc, config.equal, s)?,
-        Value::DateTime(d) => writeln!(buf, "{}{}{}", loc, config.equal, d)?,
-        Value::Array(vs) if vs.is_empty() => writeln!(buf, "{}{}[]", loc, config.equal)?,
-        Value::Array(vs) => {
-            for (i, v) in vs.iter().enumerate() {
-                inner(
-                    buf,
-                    v,
-                    config,
-                    &[loc, &config.delimiter, &i.to_string()].concat(),
-                )?
-            }
-        }
-        Value::Map(vs) if vs.is_empty() => writeln!(buf, "{}{}{{}}", loc, config.equal)?,
-        Value::Map(vs) => {
-            for (k, v) in vs.iter() {
-                inner(buf, v, config, &[loc, &config.delimiter, &k].concat())?
-            }
-        }
+        Value::Null => writeln!(buf, "{}{}null", loc, config.equal),
+        Value::Boolean(b) => writeln!(buf, "{}{}{}", loc, config.equal, b),
+        Value::Int(i) => writeln!(buf, "{}{}{}", loc, config.equal, i),
+        // Keep the decimal point to be parsed as a float again.
+        Value::Float(f) => writeln!(buf, "{}{}{:?}", loc, config.equal, f),
+        Value::String(s) => writeln!(buf, "{}{}{}", loc, config.equal, line::string(s)),
+        Value::DateTime(d) => writeln!(buf, "{}{}{}", loc, config.equal, d),
+        Value::Array(_) => writeln!(buf, "{}{}[]", loc, config.equal),
+        Value::Map(_) => writeln!(buf, "{}{}{{}}", loc, config.equal),
     }
-    Ok(())
 }
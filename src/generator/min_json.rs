@@ -1,52 +1,119 @@
 /// Minified JSON Generator.
-pub struct MinJsonGenerator;
+#[derive(Clone, Debug, Default)]
+pub struct MinJsonGenerator {
+    sort_keys: bool,
+}
+
+impl MinJsonGenerator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn sort_keys(&mut self, sort_keys: bool) -> &mut Self {
+        self.sort_keys = sort_keys;
+        self
+    }
+}
 
+use super::writer::{self, Container, Nesting, Slot, Writer};
 use crate::{Generator, Value};
 use std::io::{self, Write};
 
+impl MinJsonGenerator {
+    /// Makes a writer to write JSON incrementally. Keys are never sorted,
+    /// except in maps written as a whole by `Writer::value`.
+    pub fn writer<W: Write>(&self, buf: W) -> MinJsonWriter<'_, W> {
+        MinJsonWriter {
+            buf,
+            config: self,
+            nesting: Nesting::default(),
+        }
+    }
+}
+
 impl Generator for MinJsonGenerator {
     fn generate<W: Write>(&self, buf: &mut W, value: &Value) -> io::Result<()> {
-        inner_generate(buf, value)
+        let mut writer = self.writer(buf);
+        writer.value(value)?;
+        writer.finish()
     }
 }
 
-fn inner_generate<W: Write>(buf: &mut W, value: &Value) -> io::Result<()> {
-    match value {
-        Value::Null => write!(buf, "null")?,
-        Value::Boolean(b) => write!(buf, "{}", b)?,
-        Value::Int(i) => write!(buf, "{}", i)?,
-        Value::Float(f) if *f == f64::INFINITY => write!(buf, "Infinity")?,
-        Value::Float(f) if *f == f64::NEG_INFINITY => write!(buf, "-Infinity")?,
-        Value::Float(f) => write!(buf, "{}", f)?,
-        Value::String(s) => string(buf, s)?,
-        Value::DateTime(d) => write!(buf, "{}", d)?,
-        Value::Array(vs) => {
-            write!(buf, "[")?;
-            for (i, v) in vs.iter().enumerate() {
-                if i != 0 {
-                    write!(buf, ",")?;
-                }
-                inner_generate(buf, v)?;
-            }
-            write!(buf, "]")?;
+/// The writer made by `MinJsonGenerator::writer`.
+#[derive(Debug)]
+pub struct MinJsonWriter<'a, W> {
+    buf: W,
+    config: &'a MinJsonGenerator,
+    nesting: Nesting,
+}
+
+impl<W: Write> MinJsonWriter<'_, W> {
+    /// Checks a value can be written now, and writes the separator before it.
+    fn separate(&mut self) -> io::Result<()> {
+        match self.nesting.value()? {
+            Slot::Element(i) if i != 0 => write!(self.buf, ","),
+            _ => Ok(()),
         }
-        Value::Map(m) => {
-            write!(buf, "{{")?;
-            for (i, (k, v)) in m.iter().enumerate() {
-                if i != 0 {
-                    write!(buf, ",")?;
-                }
-                string(buf, k)?;
-                write!(buf, ":")?;
-                inner_generate(buf, v)?;
-            }
-            write!(buf, "}}")?;
+    }
+}
+
+impl<W: Write> Writer for MinJsonWriter<'_, W> {
+    fn begin_array(&mut self) -> io::Result<()> {
+        self.separate()?;
+        self.nesting.begin(Container::Array);
+        write!(self.buf, "[")
+    }
+
+    fn end_array(&mut self) -> io::Result<()> {
+        self.nesting.end(Container::Array)?;
+        write!(self.buf, "]")
+    }
+
+    fn begin_object(&mut self) -> io::Result<()> {
+        self.separate()?;
+        self.nesting.begin(Container::Object);
+        write!(self.buf, "{{")
+    }
+
+    fn key(&mut self, key: &str) -> io::Result<()> {
+        if self.nesting.key()? != 0 {
+            write!(self.buf, ",")?;
         }
+        string(&mut self.buf, key)?;
+        write!(self.buf, ":")
+    }
+
+    fn end_object(&mut self) -> io::Result<()> {
+        self.nesting.end(Container::Object)?;
+        write!(self.buf, "}}")
+    }
+
+    fn value(&mut self, value: &Value) -> io::Result<()> {
+        if let Value::Array(_) | Value::Map(_) = value {
+            return writer::container(self, value, self.config.sort_keys);
+        }
+
+        self.separate()?;
+        match value {
+            Value::Null => write!(self.buf, "null"),
+            Value::Boolean(b) => write!(self.buf, "{}", b),
+            Value::Int(i) => write!(self.buf, "{}", i),
+            Value::Float(f) if *f == f64::INFINITY => write!(self.buf, "Infinity"),
+            Value::Float(f) if *f == f64::NEG_INFINITY => write!(self.buf, "-Infinity"),
+            Value::Float(f) => write!(self.buf, "{:?}", f),
+            Value::String(s) => string(&mut self.buf, s),
+            Value::DateTime(d) => write!(self.buf, "\"{}\"", d),
+            Value::Array(_) | Value::Map(_) => unreachable!(),
+        }
+    }
+
+    fn finish(mut self) -> io::Result<()> {
+        self.nesting.finish()?;
+        self.buf.flush()
     }
-    Ok(())
 }
 
-fn string<W: Write>(buf: &mut W, s: &str) -> io::Result<()> {
+pub(super) fn string<W: Write>(buf: &mut W, s: &str) -> io::Result<()> {
     write!(buf, "\"")?;
     for c in s.chars() {
         match c {
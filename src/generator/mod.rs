@@ -1,19 +1,41 @@
 //! Generator Implementations
+mod canonical_json;
+mod csv;
+mod dotenv;
+mod ini;
 mod line;
 mod min_json;
+mod msgpack;
+mod ndjson;
 mod pretty_json;
+mod properties;
 mod toml;
+mod writer;
+mod xml;
+mod yaml;
 
-pub use line::LineGenerator;
-pub use min_json::MinJsonGenerator;
-pub use pretty_json::PrettyJsonGenerator;
+pub use canonical_json::CanonicalJsonGenerator;
+pub use csv::CsvGenerator;
+pub use dotenv::DotenvGenerator;
+pub use ini::IniGenerator;
+pub use line::{LineGenerator, LineWriter};
+pub use min_json::{MinJsonGenerator, MinJsonWriter};
+pub use msgpack::MsgpackGenerator;
+pub use ndjson::NdjsonGenerator;
+pub use pretty_json::{PrettyJsonGenerator, PrettyJsonWriter};
+pub use properties::PropertiesGenerator;
 pub use toml::TomlGenerator;
+pub use writer::Writer;
+pub use xml::XmlGenerator;
+pub use yaml::YamlGenerator;
 
-use crate::Value;
+use crate::{Map, Value};
 use std::io;
 use std::path::Path;
 
 /// The unified interface for generate data format.
+///
+/// Nothing is written when the value can't be represented in the format.
 pub trait Generator {
     fn generate<W: io::Write>(&self, buf: &mut W, value: &Value) -> io::Result<()>;
 
@@ -22,3 +44,62 @@ pub trait Generator {
         self.generate(&mut crate::io::Output::from_path(path)?, value)
     }
 }
+
+/// The error of values which can't be represented in the format.
+fn invalid<S: Into<String>>(msg: S) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+/// Collects entries of the map, sorting them by keys if `sort` is `true`.
+fn entries(map: &Map, sort: bool) -> Vec<(&String, &Value)> {
+    let mut entries = map.iter().collect::<Vec<_>>();
+    if sort {
+        entries.sort_by_key(|(k, _)| *k);
+    }
+    entries
+}
+
+/// Collects scalars and empty containers in the value by their paths, which
+/// are like the line format without the root, e.g. `user.name` and `tags.0`.
+/// The root is always expanded, even if it is empty. Keys are quoted as the
+/// line format with `equal` if needed, or joined as they are if it is `None`.
+fn flatten(flat: &mut Map, loc: Option<&str>, value: &Value, equal: Option<&str>) {
+    let path = |segment: &str| match loc {
+        Some(loc) => [loc, ".", segment].concat(),
+        None => segment.to_string(),
+    };
+    match value {
+        Value::Array(vs) if !vs.is_empty() || loc.is_none() => {
+            for (i, v) in vs.iter().enumerate() {
+                flatten(flat, Some(&path(&i.to_string())), v, equal);
+            }
+        }
+        Value::Map(m) if !m.is_empty() || loc.is_none() => {
+            for (k, v) in m {
+                let key = match equal {
+                    Some(equal) => crate::line::key(k, ".", equal),
+                    None => k.into(),
+                };
+                flatten(flat, Some(&path(&key)), v, equal);
+            }
+        }
+        _ => {
+            flat.insert(loc.unwrap_or_default().to_string(), value.clone());
+        }
+    }
+}
+
+/// Formats a scalar or an empty container as text, where null is empty.
+fn text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Boolean(b) => b.to_string(),
+        Value::Int(i) => i.to_string(),
+        // Keep the decimal point to be read as a float again.
+        Value::Float(f) => format!("{:?}", f),
+        Value::String(s) => s.clone(),
+        Value::DateTime(d) => d.to_string(),
+        Value::Array(_) => "[]".to_string(),
+        Value::Map(_) => "{}".to_string(),
+    }
+}
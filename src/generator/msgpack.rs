@@ -0,0 +1,144 @@
+//! MessagePack Generator
+use super::invalid;
+use crate::{Generator, Value};
+use std::convert::TryFrom;
+use std::io::{self, Write};
+
+/// MessagePack Generator, which writes each value in its shortest form.
+///
+/// Integers must be within `-2^63..2^64`, and floats are always written in 64
+/// bits. Date-times with offsets are written as the timestamp extension, and
+/// local ones, having no instant, as strings.
+#[derive(Copy, Clone, Debug)]
+pub struct MsgpackGenerator;
+
+impl Generator for MsgpackGenerator {
+    fn generate<W: Write>(&self, buf: &mut W, value: &Value) -> io::Result<()> {
+        let mut out = Vec::new();
+        write_value(&mut out, value)?;
+        buf.write_all(&out)?;
+        buf.flush()
+    }
+}
+
+fn write_value(buf: &mut Vec<u8>, value: &Value) -> io::Result<()> {
+    match value {
+        Value::Null => buf.push(0xc0),
+        Value::Boolean(b) => buf.push(if *b { 0xc3 } else { 0xc2 }),
+        Value::Int(i) => int(buf, *i)?,
+        Value::Float(f) => {
+            buf.push(0xcb);
+            buf.extend(&f.to_bits().to_be_bytes());
+        }
+        Value::String(s) => string(buf, s)?,
+        Value::DateTime(d) => match d.timestamp() {
+            Some((seconds, nanosecond)) => timestamp(buf, seconds, nanosecond),
+            None => string(buf, &d.to_string())?,
+        },
+        Value::Array(vs) => {
+            header(buf, vs.len(), 0x90, 0xdc)?;
+            for v in vs {
+                write_value(buf, v)?;
+            }
+        }
+        Value::Map(m) => {
+            header(buf, m.len(), 0x80, 0xde)?;
+            for (k, v) in m {
+                string(buf, k)?;
+                write_value(buf, v)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn int(buf: &mut Vec<u8>, i: i128) -> io::Result<()> {
+    if let Ok(n) = u64::try_from(i) {
+        match n {
+            0..=0x7f => buf.push(n as u8),
+            0x80..=0xff => buf.extend(&[0xcc, n as u8]),
+            0x100..=0xffff => {
+                buf.push(0xcd);
+                buf.extend(&(n as u16).to_be_bytes());
+            }
+            0x1_0000..=0xffff_ffff => {
+                buf.push(0xce);
+                buf.extend(&(n as u32).to_be_bytes());
+            }
+            _ => {
+                buf.push(0xcf);
+                buf.extend(&n.to_be_bytes());
+            }
+        }
+    } else if let Ok(n) = i64::try_from(i) {
+        match n {
+            -0x20..=-1 => buf.push(n as u8),
+            -0x80..=-0x21 => buf.extend(&[0xd0, n as u8]),
+            -0x8000..=-0x81 => {
+                buf.push(0xd1);
+                buf.extend(&(n as i16).to_be_bytes());
+            }
+            -0x8000_0000..=-0x8001 => {
+                buf.push(0xd2);
+                buf.extend(&(n as i32).to_be_bytes());
+            }
+            _ => {
+                buf.push(0xd3);
+                buf.extend(&n.to_be_bytes());
+            }
+        }
+    } else {
+        return Err(invalid("MessagePack integers must be within -2^63..2^64"));
+    }
+    Ok(())
+}
+
+fn string(buf: &mut Vec<u8>, s: &str) -> io::Result<()> {
+    match s.len() {
+        len @ 0..=0x1f => buf.push(0xa0 | len as u8),
+        len @ 0x20..=0xff => buf.extend(&[0xd9, len as u8]),
+        len => length(buf, len, 0xda)?,
+    }
+    buf.extend(s.as_bytes());
+    Ok(())
+}
+
+/// Writes the header of an array or a map of `len` elements, where `fix` is
+/// the marker of short ones and `marker` is of 16 bits lengths.
+fn header(buf: &mut Vec<u8>, len: usize, fix: u8, marker: u8) -> io::Result<()> {
+    match len {
+        0..=0xf => buf.push(fix | len as u8),
+        len => length(buf, len, marker)?,
+    }
+    Ok(())
+}
+
+/// Writes `marker` and the 16 bits length, or the next marker and the 32 bits
+/// length.
+fn length(buf: &mut Vec<u8>, len: usize, marker: u8) -> io::Result<()> {
+    if let Ok(len) = u16::try_from(len) {
+        buf.push(marker);
+        buf.extend(&len.to_be_bytes());
+    } else if let Ok(len) = u32::try_from(len) {
+        buf.push(marker + 1);
+        buf.extend(&len.to_be_bytes());
+    } else {
+        return Err(invalid("MessagePack can't have lengths over 2^32 - 1"));
+    }
+    Ok(())
+}
+
+/// Writes the timestamp extension in 32, 64 or 96 bits.
+fn timestamp(buf: &mut Vec<u8>, seconds: i64, nanosecond: u32) {
+    if nanosecond == 0 && u32::try_from(seconds).is_ok() {
+        buf.extend(&[0xd6, 0xff]);
+        buf.extend(&(seconds as u32).to_be_bytes());
+    } else if (0..1 << 34).contains(&seconds) {
+        buf.extend(&[0xd7, 0xff]);
+        buf.extend(&(u64::from(nanosecond) << 34 | seconds as u64).to_be_bytes());
+    } else {
+        buf.extend(&[0xc7, 12, 0xff]);
+        buf.extend(&nanosecond.to_be_bytes());
+        buf.extend(&seconds.to_be_bytes());
+    }
+}
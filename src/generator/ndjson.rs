@@ -0,0 +1,36 @@
+//! NDJSON (JSON Lines) Generator
+use super::{invalid, MinJsonGenerator};
+use crate::{Generator, Value};
+use std::io::{self, Write};
+
+/// NDJSON (JSON Lines) Generator, which writes each element of the root array
+/// as minified JSON in a line.
+#[derive(Clone, Debug, Default)]
+pub struct NdjsonGenerator {
+    record: MinJsonGenerator,
+}
+
+impl NdjsonGenerator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn sort_keys(&mut self, sort_keys: bool) -> &mut Self {
+        self.record.sort_keys(sort_keys);
+        self
+    }
+}
+
+impl Generator for NdjsonGenerator {
+    fn generate<W: Write>(&self, buf: &mut W, value: &Value) -> io::Result<()> {
+        let records = match value {
+            Value::Array(vs) => vs,
+            _ => return Err(invalid("The root of NDJSON must be an array")),
+        };
+        for record in records {
+            self.record.generate(buf, record)?;
+            writeln!(buf)?;
+        }
+        Ok(())
+    }
+}
@@ -1,11 +1,16 @@
 /// Formatted JSON Generator.
+#[derive(Clone, Debug)]
 pub struct PrettyJsonGenerator {
     indent: usize,
+    sort_keys: bool,
 }
 
 impl Default for PrettyJsonGenerator {
     fn default() -> Self {
-        Self { indent: 4 }
+        Self {
+            indent: 4,
+            sort_keys: false,
+        }
     }
 }
 
@@ -18,59 +23,136 @@ impl PrettyJsonGenerator {
         self.indent = indent;
         self
     }
+
+    pub fn sort_keys(&mut self, sort_keys: bool) -> &mut Self {
+        self.sort_keys = sort_keys;
+        self
+    }
 }
 
+use super::writer::{self, Container, Nesting, Slot, Writer};
 use crate::{Generator, Value};
 use std::io::{self, Write};
 
+impl PrettyJsonGenerator {
+    /// Makes a writer to write JSON incrementally. Keys are never sorted,
+    /// except in maps written as a whole by `Writer::value`.
+    pub fn writer<W: Write>(&self, buf: W) -> PrettyJsonWriter<'_, W> {
+        PrettyJsonWriter {
+            buf,
+            config: self,
+            nesting: Nesting::default(),
+        }
+    }
+}
+
 impl Generator for PrettyJsonGenerator {
     fn generate<W: Write>(&self, buf: &mut W, value: &Value) -> io::Result<()> {
-        inner_generate(buf, value, self.indent, 1)?;
-        writeln!(buf)
+        let mut writer = self.writer(buf);
+        writer.value(value)?;
+        writer.finish()
     }
 }
 
-fn inner_generate<W: Write>(
-    buf: &mut W,
-    value: &Value,
-    ind_size: usize,
-    ind: usize,
-) -> io::Result<()> {
-    match value {
-        Value::Null => write!(buf, "null")?,
-        Value::Boolean(b) => write!(buf, "{}", b)?,
-        Value::Int(i) => write!(buf, "{}", i)?,
-        Value::Float(f) if *f == f64::INFINITY => write!(buf, "Infinity")?,
-        Value::Float(f) if *f == f64::NEG_INFINITY => write!(buf, "-Infinity")?,
-        Value::Float(f) => write!(buf, "{}", f)?,
-        Value::String(s) => string(buf, s)?,
-        Value::DateTime(d) => write!(buf, "\"{}\"", d)?,
-        Value::Array(vs) => {
-            writeln!(buf, "[")?;
-            for (i, v) in vs.iter().enumerate() {
-                if i != 0 {
-                    writeln!(buf, ",")?;
-                }
-                write!(buf, "{}", " ".repeat(ind_size * ind))?;
-                inner_generate(buf, v, ind_size, ind + 1)?;
+/// The writer made by `PrettyJsonGenerator::writer`.
+#[derive(Debug)]
+pub struct PrettyJsonWriter<'a, W> {
+    buf: W,
+    config: &'a PrettyJsonGenerator,
+    nesting: Nesting,
+}
+
+impl<W: Write> PrettyJsonWriter<'_, W> {
+    fn indent(&mut self) -> io::Result<()> {
+        write!(
+            self.buf,
+            "{}",
+            " ".repeat(self.config.indent * self.nesting.depth())
+        )
+    }
+
+    /// Checks a value can be written now, and writes the separator before it.
+    fn separate(&mut self) -> io::Result<()> {
+        if let Slot::Element(i) = self.nesting.value()? {
+            if i != 0 {
+                writeln!(self.buf, ",")?;
             }
-            write!(buf, "\n{}]", " ".repeat(ind_size * (ind - 1)))?;
+            self.indent()?;
         }
-        Value::Map(m) => {
-            writeln!(buf, "{{")?;
-            for (i, (k, v)) in m.iter().enumerate() {
-                if i != 0 {
-                    writeln!(buf, ",")?;
-                }
-                write!(buf, "{}", " ".repeat(ind_size * ind))?;
-                string(buf, k)?;
-                write!(buf, ": ")?;
-                inner_generate(buf, v, ind_size, ind + 1)?;
-            }
-            write!(buf, "\n{}}}", " ".repeat(ind_size * (ind - 1)))?;
+        Ok(())
+    }
+
+    /// Writes the line break after the root value.
+    fn terminate(&mut self) -> io::Result<()> {
+        if self.nesting.is_done() {
+            writeln!(self.buf)?;
         }
+        Ok(())
+    }
+
+    fn end(&mut self, container: Container, bracket: char) -> io::Result<()> {
+        self.nesting.end(container)?;
+        writeln!(self.buf)?;
+        self.indent()?;
+        write!(self.buf, "{}", bracket)?;
+        self.terminate()
+    }
+}
+
+impl<W: Write> Writer for PrettyJsonWriter<'_, W> {
+    fn begin_array(&mut self) -> io::Result<()> {
+        self.separate()?;
+        self.nesting.begin(Container::Array);
+        writeln!(self.buf, "[")
+    }
+
+    fn end_array(&mut self) -> io::Result<()> {
+        self.end(Container::Array, ']')
+    }
+
+    fn begin_object(&mut self) -> io::Result<()> {
+        self.separate()?;
+        self.nesting.begin(Container::Object);
+        writeln!(self.buf, "{{")
+    }
+
+    fn key(&mut self, key: &str) -> io::Result<()> {
+        if self.nesting.key()? != 0 {
+            writeln!(self.buf, ",")?;
+        }
+        self.indent()?;
+        string(&mut self.buf, key)?;
+        write!(self.buf, ": ")
+    }
+
+    fn end_object(&mut self) -> io::Result<()> {
+        self.end(Container::Object, '}')
+    }
+
+    fn value(&mut self, value: &Value) -> io::Result<()> {
+        if let Value::Array(_) | Value::Map(_) = value {
+            return writer::container(self, value, self.config.sort_keys);
+        }
+
+        self.separate()?;
+        match value {
+            Value::Null => write!(self.buf, "null")?,
+            Value::Boolean(b) => write!(self.buf, "{}", b)?,
+            Value::Int(i) => write!(self.buf, "{}", i)?,
+            Value::Float(f) if *f == f64::INFINITY => write!(self.buf, "Infinity")?,
+            Value::Float(f) if *f == f64::NEG_INFINITY => write!(self.buf, "-Infinity")?,
+            Value::Float(f) => write!(self.buf, "{:?}", f)?,
+            Value::String(s) => string(&mut self.buf, s)?,
+            Value::DateTime(d) => write!(self.buf, "\"{}\"", d)?,
+            Value::Array(_) | Value::Map(_) => unreachable!(),
+        }
+        self.terminate()
+    }
+
+    fn finish(mut self) -> io::Result<()> {
+        self.nesting.finish()?;
+        self.buf.flush()
     }
-    Ok(())
 }
 
 fn string<W: Write>(buf: &mut W, s: &str) -> io::Result<()> {
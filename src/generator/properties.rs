@@ -0,0 +1,68 @@
+//! Java Properties Generator
+use super::{flatten, invalid, text};
+use crate::{Generator, Map, Value};
+use std::io::{self, Write};
+
+/// Java `.properties` Generator, which flattens nested values into keys like
+/// `server.hosts.0`.
+///
+/// The root must be a map or an array. Characters out of printable ASCII are
+/// escaped as `\uXXXX`, as Java reads properties in ISO 8859-1.
+#[derive(Clone, Debug, Default)]
+pub struct PropertiesGenerator {
+    expand_keys: bool,
+}
+
+impl PropertiesGenerator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Quotes keys containing `.` or looking like array indices, to be read
+    /// back by `PropertiesParser` with `expand_keys`.
+    pub fn expand_keys(&mut self, expand_keys: bool) -> &mut Self {
+        self.expand_keys = expand_keys;
+        self
+    }
+}
+
+impl Generator for PropertiesGenerator {
+    fn generate<W: Write>(&self, buf: &mut W, value: &Value) -> io::Result<()> {
+        if !matches!(value, Value::Array(_) | Value::Map(_)) {
+            return Err(invalid("The root of properties must be a map or an array"));
+        }
+        let mut flat = Map::new();
+        let equal = if self.expand_keys { Some("\n") } else { None };
+        flatten(&mut flat, None, value, equal);
+        // Not to write a part of the output on errors.
+        let mut out = Vec::new();
+        for (k, v) in &flat {
+            writeln!(out, "{}={}", escape(k, true), escape(&text(v), false))?;
+        }
+        buf.write_all(&out)?;
+        buf.flush()
+    }
+}
+
+/// Escapes the key or the value, where spaces are escaped only at the head of
+/// values.
+fn escape(s: &str, key: bool) -> String {
+    let mut escaped = String::new();
+    for (i, c) in s.chars().enumerate() {
+        match c {
+            ' ' if key || i == 0 => escaped.push_str("\\ "),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\u{c}' => escaped.push_str("\\f"),
+            '\\' | '=' | ':' | '#' | '!' => escaped.extend(&['\\', c]),
+            ' '..='~' => escaped.push(c),
+            _ => {
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    escaped.push_str(&format!("\\u{:04X}", unit));
+                }
+            }
+        }
+    }
+    escaped
+}
@@ -1,13 +1,174 @@
-//!TOML Generator
+//! TOML Generator
 
-use crate::{Value, Generator};
-use std::io::Write;
+use super::invalid;
+use crate::{Generator, Map, Value};
+use std::convert::TryFrom;
+use std::io::{self, Write};
 
+/// TOML Generator.
+///
+/// The root value must be a map, and `Value::Null` can't be contained anywhere.
+/// Integers must fit in 64 bits.
+/// Maps are written as `[table]`s and arrays of maps as `[[array.of.tables]]`,
+/// except inside arrays mixing maps with other values, which are written inline.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct TomlGenerator;
 
 impl Generator for TomlGenerator {
-    fn generate<W: Write>(&self, _buf: &mut W, _value: &Value) -> std::io::Result<()> {
-        Ok(())
+    fn generate<W: Write>(&self, buf: &mut W, value: &Value) -> io::Result<()> {
+        let map = match value {
+            Value::Map(m) => m,
+            _ => return Err(invalid("The root of TOML must be a table")),
+        };
+
+        // Generate into the memory first, not to leave a broken output on errors.
+        let mut out = Vec::new();
+        table(&mut out, map, &mut Vec::new(), false)?;
+        buf.write_all(&out)
+    }
+}
+
+fn is_table(value: &Value) -> bool {
+    match value {
+        Value::Map(_) => true,
+        Value::Array(vs) => !vs.is_empty() && vs.iter().all(|v| matches!(v, Value::Map(_))),
+        _ => false,
+    }
+}
+
+fn table(buf: &mut Vec<u8>, map: &Map, path: &mut Vec<String>, array: bool) -> io::Result<()> {
+    let (tables, values): (Vec<_>, Vec<_>) = map.iter().partition(|(_, v)| is_table(v));
+
+    // Headers of tables only containing sub-tables are omitted.
+    if !path.is_empty() && (array || !values.is_empty() || tables.is_empty()) {
+        if !buf.is_empty() {
+            writeln!(buf)?;
+        }
+        if array {
+            writeln!(buf, "[[{}]]", dotted(path))?;
+        } else {
+            writeln!(buf, "[{}]", dotted(path))?;
+        }
+    }
+
+    for (k, v) in values {
+        path.push(k.clone());
+        key(buf, k)?;
+        write!(buf, " = ")?;
+        inline(buf, v, path)?;
+        writeln!(buf)?;
+        path.pop();
+    }
+
+    for (k, v) in tables {
+        path.push(k.clone());
+        match v {
+            Value::Map(m) => table(buf, m, path, false)?,
+            Value::Array(vs) => {
+                for v in vs {
+                    if let Value::Map(m) = v {
+                        table(buf, m, path, true)?;
+                    }
+                }
+            }
+            _ => unreachable!(),
+        }
+        path.pop();
+    }
+
+    Ok(())
+}
+
+fn inline<W: Write>(buf: &mut W, value: &Value, path: &[String]) -> io::Result<()> {
+    match value {
+        Value::Null => {
+            return Err(invalid(format!(
+                "TOML can't represent null (at `{}`)",
+                dotted(path)
+            )))
+        }
+        Value::Boolean(b) => write!(buf, "{}", b)?,
+        Value::Int(i) => match i64::try_from(*i) {
+            Ok(i) => write!(buf, "{}", i)?,
+            Err(_) => {
+                return Err(invalid(format!(
+                    "TOML can't represent {} out of 64 bit integers (at `{}`)",
+                    i,
+                    dotted(path)
+                )))
+            }
+        },
+        Value::Float(f) if f.is_nan() => write!(buf, "nan")?,
+        Value::Float(f) if *f == f64::INFINITY => write!(buf, "inf")?,
+        Value::Float(f) if *f == f64::NEG_INFINITY => write!(buf, "-inf")?,
+        Value::Float(f) => write!(buf, "{:?}", f)?,
+        Value::String(s) => string(buf, s)?,
+        Value::DateTime(d) => write!(buf, "{}", d)?,
+        Value::Array(vs) => {
+            write!(buf, "[")?;
+            for (i, v) in vs.iter().enumerate() {
+                if i != 0 {
+                    write!(buf, ", ")?;
+                }
+                inline(buf, v, path)?;
+            }
+            write!(buf, "]")?;
+        }
+        Value::Map(m) if m.is_empty() => write!(buf, "{{}}")?,
+        Value::Map(m) => {
+            write!(buf, "{{ ")?;
+            for (i, (k, v)) in m.iter().enumerate() {
+                if i != 0 {
+                    write!(buf, ", ")?;
+                }
+                key(buf, k)?;
+                write!(buf, " = ")?;
+                inline(buf, v, &[path, std::slice::from_ref(k)].concat())?;
+            }
+            write!(buf, " }}")?;
+        }
+    }
+    Ok(())
+}
+
+fn dotted(path: &[String]) -> String {
+    let mut buf = Vec::new();
+    for (i, k) in path.iter().enumerate() {
+        if i != 0 {
+            buf.push(b'.');
+        }
+        // Writing to `Vec` never fails.
+        key(&mut buf, k).unwrap();
+    }
+    String::from_utf8(buf).unwrap()
+}
+
+fn key<W: Write>(buf: &mut W, k: &str) -> io::Result<()> {
+    if !k.is_empty()
+        && k.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        write!(buf, "{}", k)
+    } else {
+        string(buf, k)
+    }
+}
+
+fn string<W: Write>(buf: &mut W, s: &str) -> io::Result<()> {
+    write!(buf, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(buf, "\\\"")?,
+            '\\' => write!(buf, "\\\\")?,
+            '\x08' => write!(buf, "\\b")?,
+            '\x0c' => write!(buf, "\\f")?,
+            '\n' => write!(buf, "\\n")?,
+            '\r' => write!(buf, "\\r")?,
+            '\t' => write!(buf, "\\t")?,
+            c if c < ' ' || c == '\x7f' => write!(buf, "\\u{:04X}", c as u32)?,
+            c => write!(buf, "{}", c)?,
+        }
     }
+    write!(buf, "\"")?;
+    Ok(())
 }
@@ -0,0 +1,165 @@
+//! Writer Interface
+use crate::Value;
+use std::io;
+
+/// The writer-style interface of generators, to write a document incrementally
+/// without building a whole `Value`.
+///
+/// Calls must form exactly one value: an array is written by `begin_array`,
+/// its elements and `end_array`, and an object by `begin_object`, pairs of
+/// `key` and its value, and `end_object`. Calls out of this order fail with
+/// `io::ErrorKind::InvalidInput`.
+pub trait Writer {
+    fn begin_array(&mut self) -> io::Result<()>;
+
+    fn end_array(&mut self) -> io::Result<()>;
+
+    fn begin_object(&mut self) -> io::Result<()>;
+
+    fn key(&mut self, key: &str) -> io::Result<()>;
+
+    fn end_object(&mut self) -> io::Result<()>;
+
+    /// Writes a value, which can also be a whole array or map.
+    fn value(&mut self, value: &Value) -> io::Result<()>;
+
+    /// Checks the value has been completed, and flushes the output.
+    fn finish(self) -> io::Result<()>
+    where
+        Self: Sized;
+}
+
+/// Writes an array or a map by calls to the writer.
+pub(super) fn container<V: Writer>(
+    writer: &mut V,
+    value: &Value,
+    sort_keys: bool,
+) -> io::Result<()> {
+    match value {
+        Value::Array(vs) => {
+            writer.begin_array()?;
+            for v in vs {
+                writer.value(v)?;
+            }
+            writer.end_array()
+        }
+        Value::Map(m) => {
+            writer.begin_object()?;
+            for (k, v) in super::entries(m, sort_keys) {
+                writer.key(k)?;
+                writer.value(v)?;
+            }
+            writer.end_object()
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(super) enum Container {
+    Array,
+    Object,
+}
+
+/// Where a value is written.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(super) enum Slot {
+    Root,
+    /// An element of an array, with its index.
+    Element(usize),
+    /// A value following a key.
+    Entry,
+}
+
+#[derive(Clone, Debug)]
+struct Frame {
+    container: Container,
+    len: usize,
+    /// Whether a key is waiting for its value.
+    key: bool,
+}
+
+/// Tracks the nesting of containers, to check the order of calls to writers.
+#[derive(Clone, Debug, Default)]
+pub(super) struct Nesting {
+    stack: Vec<Frame>,
+    started: bool,
+}
+
+fn misuse(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+impl Nesting {
+    /// Checks a value can be written now, and returns where it is.
+    pub(super) fn value(&mut self) -> io::Result<Slot> {
+        match self.stack.last_mut() {
+            None if self.started => Err(misuse("The value has already been written")),
+            None => {
+                self.started = true;
+                Ok(Slot::Root)
+            }
+            Some(frame) if frame.container == Container::Array => {
+                frame.len += 1;
+                Ok(Slot::Element(frame.len - 1))
+            }
+            Some(frame) if frame.key => {
+                frame.key = false;
+                Ok(Slot::Entry)
+            }
+            Some(_) => Err(misuse("A key is expected before the value")),
+        }
+    }
+
+    /// Checks a key can be written now, and returns its index.
+    pub(super) fn key(&mut self) -> io::Result<usize> {
+        match self.stack.last_mut() {
+            Some(frame) if frame.container == Container::Object && !frame.key => {
+                frame.key = true;
+                frame.len += 1;
+                Ok(frame.len - 1)
+            }
+            Some(frame) if frame.key => Err(misuse("A value is expected after the key")),
+            _ => Err(misuse("A key is written outside of objects")),
+        }
+    }
+
+    /// Enters the container, after `value`.
+    pub(super) fn begin(&mut self, container: Container) {
+        self.stack.push(Frame {
+            container,
+            len: 0,
+            key: false,
+        });
+    }
+
+    /// Leaves the container, returning its length.
+    pub(super) fn end(&mut self, container: Container) -> io::Result<usize> {
+        match self.stack.last() {
+            Some(frame) if frame.key => Err(misuse("A value is expected after the key")),
+            Some(frame) if frame.container == container => Ok(self.stack.pop().unwrap().len),
+            _ => Err(misuse(match container {
+                Container::Array => "No array to end",
+                Container::Object => "No object to end",
+            })),
+        }
+    }
+
+    /// The number of containers being written.
+    pub(super) fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Whether the whole value has been written.
+    pub(super) fn is_done(&self) -> bool {
+        self.started && self.stack.is_empty()
+    }
+
+    pub(super) fn finish(&self) -> io::Result<()> {
+        if self.is_done() {
+            Ok(())
+        } else {
+            Err(misuse("The value is incomplete"))
+        }
+    }
+}
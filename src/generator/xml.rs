@@ -0,0 +1,159 @@
+//! XML Generator
+use super::{invalid, text};
+use crate::xml::{is_char, is_qualified_name};
+use crate::{Generator, Map, Value};
+use std::io::{self, Write};
+
+/// XML Generator, which maps `Value` onto XML in the same way as `XmlParser`.
+///
+/// The root must be a map with a single key, the name of the root element.
+/// `@name` keys are attributes, `#text` is the text, and arrays are repeated
+/// elements. Null, empty strings and empty maps are empty elements. Elements
+/// having both text and child elements are written in a line, not to add
+/// whitespace to their text.
+#[derive(Clone, Debug)]
+pub struct XmlGenerator {
+    indent: usize,
+    declaration: bool,
+}
+
+impl Default for XmlGenerator {
+    fn default() -> Self {
+        Self {
+            indent: 2,
+            declaration: true,
+        }
+    }
+}
+
+impl XmlGenerator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the indent size, where `0` writes the whole document in a line.
+    pub fn indent(&mut self, indent: usize) -> &mut Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Writes the XML declaration, `<?xml version="1.0" encoding="UTF-8"?>`.
+    pub fn declaration(&mut self, declaration: bool) -> &mut Self {
+        self.declaration = declaration;
+        self
+    }
+}
+
+impl Generator for XmlGenerator {
+    fn generate<W: Write>(&self, buf: &mut W, value: &Value) -> io::Result<()> {
+        let (name, root) = match value {
+            Value::Map(m) if m.len() == 1 => m.iter().next().unwrap(),
+            _ => return Err(invalid("The root of XML must be a map of a single element")),
+        };
+        if let Value::Array(_) = root {
+            return Err(invalid("The root element of XML can't be an array"));
+        }
+        let mut out = Vec::new();
+        if self.declaration {
+            writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        }
+        let depth = if self.indent == 0 { None } else { Some(0) };
+        self.element(&mut out, name, root, depth)?;
+        writeln!(out)?;
+        buf.write_all(&out)
+    }
+}
+
+impl XmlGenerator {
+    /// Writes an element, indenting child elements by `depth` if it's some.
+    fn element(
+        &self,
+        buf: &mut Vec<u8>,
+        name: &str,
+        value: &Value,
+        depth: Option<usize>,
+    ) -> io::Result<()> {
+        if !is_qualified_name(name) {
+            return Err(invalid("Keys of XML must be names, or `@` and names"));
+        }
+        let empty = Map::new();
+        let map = match value {
+            Value::Array(_) => return Err(invalid("XML can't have nested arrays")),
+            Value::Map(m) => m,
+            _ => &empty,
+        };
+
+        write!(buf, "<{}", name)?;
+        let mut content = match value {
+            Value::Map(_) => String::new(),
+            v => text(v),
+        };
+        let mut children = Vec::new();
+        for (k, v) in map {
+            if let Some(attribute) = k.strip_prefix('@') {
+                if !is_qualified_name(attribute) {
+                    return Err(invalid("Keys of XML must be names, or `@` and names"));
+                }
+                write!(buf, " {}=\"{}\"", attribute, escape(&scalar(v)?, true)?)?;
+            } else if k == "#text" {
+                content = scalar(v)?;
+            } else {
+                match v {
+                    Value::Array(vs) => children.extend(vs.iter().map(|v| (k, v))),
+                    v => children.push((k, v)),
+                }
+            }
+        }
+
+        if content.is_empty() && children.is_empty() {
+            return write!(buf, "/>");
+        }
+        write!(buf, ">{}", escape(&content, false)?)?;
+        // Indentation would be a part of the text.
+        let depth = depth.filter(|_| content.is_empty());
+        for (k, v) in &children {
+            if let Some(depth) = depth {
+                write!(buf, "\n{:1$}", "", (depth + 1) * self.indent)?;
+            }
+            self.element(buf, k, v, depth.map(|d| d + 1))?;
+        }
+        match depth {
+            Some(depth) if !children.is_empty() => {
+                write!(buf, "\n{:1$}</{2}>", "", depth * self.indent, name)
+            }
+            _ => write!(buf, "</{}>", name),
+        }
+    }
+}
+
+/// Formats the value of an attribute or a text.
+fn scalar(value: &Value) -> io::Result<String> {
+    match value {
+        Value::Array(_) | Value::Map(_) => {
+            Err(invalid("Attributes and texts of XML must be scalars"))
+        }
+        v => Ok(text(v)),
+    }
+}
+
+/// Escapes markup, and whitespace normalized in attributes.
+fn escape(s: &str, attribute: bool) -> io::Result<String> {
+    let mut escaped = String::new();
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' if attribute => escaped.push_str("&quot;"),
+            '\t' | '\n' if attribute => escaped.push_str(&format!("&#{};", c as u32)),
+            '\r' => escaped.push_str("&#13;"),
+            c if is_char(c) => escaped.push(c),
+            _ => {
+                return Err(invalid(
+                    "XML can't have control characters except tabs and line breaks",
+                ))
+            }
+        }
+    }
+    Ok(escaped)
+}
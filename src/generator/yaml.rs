@@ -0,0 +1,174 @@
+//! YAML Generator
+use super::entries;
+use crate::yaml::{is_plain, quoted, timestamp};
+use crate::{Generator, Value};
+use std::io::{self, Write};
+
+/// Block style YAML Generator.
+///
+/// Strings which would be read as other types are double quoted, and
+/// multi-line strings are written as literal blocks. Empty arrays and maps are
+/// written as `[]` and `{}`.
+#[derive(Clone, Debug)]
+pub struct YamlGenerator {
+    indent: usize,
+    sort_keys: bool,
+}
+
+impl Default for YamlGenerator {
+    fn default() -> Self {
+        Self {
+            indent: 2,
+            sort_keys: false,
+        }
+    }
+}
+
+impl YamlGenerator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the indent size, which is limited from 2 to 9.
+    pub fn indent(&mut self, indent: usize) -> &mut Self {
+        self.indent = indent.clamp(2, 9);
+        self
+    }
+
+    pub fn sort_keys(&mut self, sort_keys: bool) -> &mut Self {
+        self.sort_keys = sort_keys;
+        self
+    }
+}
+
+impl Generator for YamlGenerator {
+    fn generate<W: Write>(&self, buf: &mut W, value: &Value) -> io::Result<()> {
+        let mut out = Vec::new();
+        match value {
+            Value::Array(vs) if !vs.is_empty() => self.node(&mut out, value, 0)?,
+            Value::Map(m) if !m.is_empty() => self.node(&mut out, value, 0)?,
+            // Indented not to be confused with document markers.
+            _ => self.scalar(&mut out, value, self.indent)?,
+        }
+        buf.write_all(&out)
+    }
+}
+
+impl YamlGenerator {
+    /// Writes a node at `column`, after the indentation or `- ` of the line.
+    fn node(&self, buf: &mut Vec<u8>, value: &Value, column: usize) -> io::Result<()> {
+        match value {
+            Value::Array(vs) if !vs.is_empty() => {
+                for (i, v) in vs.iter().enumerate() {
+                    if i != 0 {
+                        write!(buf, "{:1$}", "", column)?;
+                    }
+                    write!(buf, "-{:1$}", "", self.indent - 1)?;
+                    self.node(buf, v, column + self.indent)?;
+                }
+            }
+            Value::Map(m) if !m.is_empty() => {
+                for (i, (k, v)) in entries(m, self.sort_keys).into_iter().enumerate() {
+                    if i != 0 {
+                        write!(buf, "{:1$}", "", column)?;
+                    }
+                    if is_plain(k) {
+                        write!(buf, "{}:", k)?;
+                    } else {
+                        write!(buf, "{}:", quoted(k))?;
+                    }
+                    match v {
+                        Value::Array(vs) if !vs.is_empty() => {
+                            write!(buf, "\n{:1$}", "", column + self.indent)?
+                        }
+                        Value::Map(m) if !m.is_empty() => {
+                            write!(buf, "\n{:1$}", "", column + self.indent)?
+                        }
+                        _ => write!(buf, " ")?,
+                    }
+                    self.node(buf, v, column + self.indent)?;
+                }
+            }
+            _ => self.scalar(buf, value, column)?,
+        }
+        Ok(())
+    }
+
+    /// Writes a scalar or an empty collection in a line, where lines of literal
+    /// blocks are indented to `column`.
+    fn scalar(&self, buf: &mut Vec<u8>, value: &Value, column: usize) -> io::Result<()> {
+        match value {
+            Value::Null => write!(buf, "null")?,
+            Value::Boolean(b) => write!(buf, "{}", b)?,
+            Value::Int(i) => write!(buf, "{}", i)?,
+            Value::Float(f) => write!(buf, "{}", float(*f))?,
+            Value::String(s) if is_plain(s) => write!(buf, "{}", s)?,
+            Value::String(s) if is_literal(s) => self.literal(buf, s, column)?,
+            Value::String(s) => write!(buf, "{}", quoted(s))?,
+            // Times without dates are not timestamps of YAML.
+            Value::DateTime(d) => match d.to_string() {
+                d if timestamp(&d).is_some() => write!(buf, "{}", d)?,
+                d => write!(buf, "{}", quoted(&d))?,
+            },
+            Value::Array(_) => write!(buf, "[]")?,
+            Value::Map(_) => write!(buf, "{{}}")?,
+        }
+        writeln!(buf)
+    }
+
+    /// Writes a literal block, choosing the chomping by trailing line breaks.
+    fn literal(&self, buf: &mut Vec<u8>, s: &str, column: usize) -> io::Result<()> {
+        let body = s.trim_end_matches('\n');
+        let breaks = s.len() - body.len();
+        write!(buf, "|")?;
+        // The indentation can't be detected by lines starting with spaces.
+        if body.trim_start_matches('\n').starts_with(' ') {
+            write!(buf, "{}", self.indent)?;
+        }
+        match breaks {
+            0 => write!(buf, "-")?,
+            1 => {}
+            _ => write!(buf, "+")?,
+        }
+        for line in body.split('\n') {
+            writeln!(buf)?;
+            if !line.is_empty() {
+                write!(buf, "{:2$}{}", "", line, column)?;
+            }
+        }
+        for _ in 1..breaks {
+            writeln!(buf)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether the string can be written as a literal block.
+fn is_literal(s: &str) -> bool {
+    s.contains('\n')
+        && !s.trim_matches('\n').is_empty()
+        && !s.chars().any(|c| {
+            c != '\n' && c != '\t' && c.is_control()
+                || matches!(c, '\u{2028}' | '\u{2029}' | '\u{feff}')
+        })
+}
+
+/// Formats the float as YAML 1.1 also reads it, with a period and a signed
+/// exponent.
+fn float(f: f64) -> String {
+    if f.is_nan() {
+        return ".nan".to_string();
+    } else if f.is_infinite() {
+        return if f > 0.0 { ".inf" } else { "-.inf" }.to_string();
+    }
+    let s = format!("{:?}", f);
+    match s.find('e') {
+        Some(i) => {
+            let (mantissa, exponent) = (&s[..i], &s[i + 1..]);
+            let period = if mantissa.contains('.') { "" } else { ".0" };
+            let sign = if exponent.starts_with('-') { "" } else { "+" };
+            format!("{}{}e{}{}", mantissa, period, sign, exponent)
+        }
+        None => s,
+    }
+}
@@ -61,3 +61,40 @@ impl Output {
         }
     }
 }
+
+/// The whole input read in the memory, to point errors in it.
+#[derive(Clone, Debug)]
+pub struct Source {
+    /// The file name, or `<stdin>` for the standard input.
+    pub name: String,
+    /// The raw bytes, which text parsers check to be UTF-8 by `parse_bytes`.
+    pub bytes: Vec<u8>,
+}
+
+impl Source {
+    pub fn from_path<P: AsRef<Path>>(path: Option<P>) -> io::Result<Self> {
+        let name = name(path.as_ref());
+        let mut bytes = Vec::new();
+        io::Read::read_to_end(&mut Input::from_path(path)?, &mut bytes)?;
+        Ok(Self { name, bytes })
+    }
+
+    /// Prints the diagnostic of the error to the standard error.
+    pub fn report(&self, e: &crate::Error) {
+        let text = String::from_utf8_lossy(&self.bytes);
+        eprint!("{}", e.render(&text, &self.name, color()));
+    }
+}
+
+/// The name of the input file to show, or `<stdin>` for the standard input.
+pub fn name<P: AsRef<Path>>(path: Option<P>) -> String {
+    match &path {
+        Some(path) if path.as_ref() != Path::new("-") => path.as_ref().display().to_string(),
+        _ => "<stdin>".to_string(),
+    }
+}
+
+/// Whether the standard error should be colored.
+pub fn color() -> bool {
+    io::IsTerminal::is_terminal(&io::stderr()) && std::env::var_os("NO_COLOR").is_none()
+}
@@ -1,16 +1,24 @@
 //! Simple CLI Parser for Data Formats
+pub mod datetime;
+mod error;
 pub mod generator;
+mod line;
 pub mod parser;
-pub mod datetime;
+mod xml;
+mod yaml;
 
 #[cfg(feature = "bin")]
 pub mod io;
 
+pub use error::Error;
 pub use generator::Generator;
 pub use parser::Parser;
 
-use std::collections::HashMap;
 use datetime::DateTime;
+use indexmap::IndexMap;
+
+/// The map type of `Value::Map`, which keeps the insertion order of keys.
+pub type Map = IndexMap<String, Value>;
 
 /// Represents the Parsed value.
 #[derive(Clone, Debug)]
@@ -22,5 +30,5 @@ pub enum Value {
     String(String),
     DateTime(DateTime),
     Array(Vec<Value>),
-    Map(HashMap<String, Value>),
+    Map(Map),
 }
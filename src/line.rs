@@ -0,0 +1,112 @@
+//! Quoting Scheme of the Line Format
+//!
+//! Strings are always quoted by `"`, escaping `"`, `\`, NUL, LF, CR and tab by a
+//! backslash, and other control characters as `\u{7f}`.
+//!
+//! Map keys are written as they are, unless they are empty, consist of digits
+//! (which are read as array indices), start with `"`, contain control
+//! characters, or could be confused with the delimiter or the equal sign. Such
+//! keys are quoted in the same way as strings.
+//!
+//! With bracket paths, array indices are written as `[0]`, and map keys as
+//! `.key` if they are identifiers, or as `["key"]` quoted in the same way as
+//! strings otherwise, like `$.users[0]["first.name"]`.
+use std::borrow::Cow;
+
+/// Characters escaped by a backslash, and the characters following it.
+const ESCAPES: [(char, char); 6] = [
+    ('"', '"'),
+    ('\\', '\\'),
+    ('\0', '0'),
+    ('\n', 'n'),
+    ('\r', 'r'),
+    ('\t', 't'),
+];
+
+/// Reads the character following a backslash.
+pub(crate) fn unescape(c: char) -> Option<char> {
+    ESCAPES.iter().find(|(_, e)| *e == c).map(|(c, _)| *c)
+}
+
+/// Quotes and escapes the string.
+pub(crate) fn string(s: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        match ESCAPES.iter().find(|(raw, _)| *raw == c) {
+            Some((_, e)) => quoted.extend(&['\\', *e]),
+            None if c.is_control() => quoted.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            None => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Quotes the map key only if needed.
+pub(crate) fn key<'a>(key: &'a str, delimiter: &str, equal: &str) -> Cow<'a, str> {
+    if is_plain_key(key, delimiter, equal) {
+        Cow::Borrowed(key)
+    } else {
+        Cow::Owned(string(key))
+    }
+}
+
+/// Writes the map key as a segment of bracket paths, following the delimiter.
+pub(crate) fn bracket_key(key: &str, delimiter: &str, equal: &str) -> String {
+    if is_identifier(key) && is_plain_key(key, delimiter, equal) {
+        [delimiter, key].concat()
+    } else {
+        format!("[{}]", string(key))
+    }
+}
+
+/// Whether the key is an identifier of JavaScript, roughly.
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    let head = |c: char| c.is_alphabetic() || c == '_' || c == '$';
+    chars.next().is_some_and(head) && chars.all(|c| head(c) || c.is_numeric())
+}
+
+/// Whether the key can be read back without quotes.
+fn is_plain_key(key: &str, delimiter: &str, equal: &str) -> bool {
+    // The first occurrence of the separators must be just after the key,
+    // including ones made of the end of the key and the head of the separator.
+    let separated = |sep: &str| [key, sep].concat().find(sep) == Some(key.len());
+
+    !key.is_empty()
+        && !key.bytes().all(|b| b.is_ascii_digit())
+        && !key.starts_with('"')
+        && !key.chars().any(char::is_control)
+        && separated(delimiter)
+        && separated(equal)
+}
+
+/// Reads a quoted string at the head of `s`, returning it and the length read.
+pub(crate) fn unquote(s: &str) -> Option<(String, usize)> {
+    let mut unquoted = String::new();
+    let mut chars = s.char_indices();
+    if chars.next()? != (0, '"') {
+        return None;
+    }
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((unquoted, i + 1)),
+            '\\' => match chars.next()?.1 {
+                '\'' => unquoted.push('\''),
+                'u' => {
+                    let rest = chars.as_str().strip_prefix('{')?;
+                    let hex = &rest[..rest.find('}')?];
+                    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                        return None;
+                    }
+                    let code = u32::from_str_radix(hex, 16).ok()?;
+                    unquoted.push(std::char::from_u32(code)?);
+                    chars.nth(hex.len() + 1);
+                }
+                e => unquoted.push(unescape(e)?),
+            },
+            c => unquoted.push(c),
+        }
+    }
+    None
+}
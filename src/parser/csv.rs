@@ -0,0 +1,183 @@
+//! CSV Parser
+use crate::{Error, Map, Parser, Value};
+
+/// CSV (RFC 4180) Parser, which reads records as an array of maps keyed by the
+/// header row.
+///
+/// Both CRLF and LF are accepted as line breaks, and blank lines are ignored.
+/// Fields are strings, unless `infer_types` is set.
+#[derive(Clone, Debug)]
+pub struct CsvParser {
+    delimiter: char,
+    infer_types: bool,
+}
+
+impl Default for CsvParser {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            infer_types: false,
+        }
+    }
+}
+
+impl CsvParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the delimiter of fields, like `'\t'` for TSV.
+    pub fn delimiter(&mut self, delimiter: char) -> &mut Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Reads empty fields as null, and fields like `true`, `-1` and `1.5e3` as
+    /// booleans and numbers.
+    pub fn infer_types(&mut self, infer_types: bool) -> &mut Self {
+        self.infer_types = infer_types;
+        self
+    }
+}
+
+/// A field and its offset in the input.
+struct Field {
+    offset: usize,
+    text: String,
+}
+
+/// A record, with the offset of its end.
+struct Record {
+    fields: Vec<Field>,
+    end: usize,
+}
+
+impl Parser for CsvParser {
+    const FORMAT: &'static str = "CSV";
+
+    fn parse(&self, s: &str) -> Result<Value, Error> {
+        let mut records = self.records(s)?.into_iter();
+        let header = match records.next() {
+            Some(header) => header.fields,
+            None => return Ok(Value::Array(Vec::new())),
+        };
+        for (i, field) in header.iter().enumerate() {
+            if header[..i].iter().any(|f| f.text == field.text) {
+                return Err(Error::new("CSV", s, field.offset, &["unique header"]));
+            }
+        }
+
+        let mut values = Vec::new();
+        for record in records {
+            if record.fields.len() < header.len() {
+                return Err(Error::new("CSV", s, record.end, &["delimiter"]));
+            } else if let Some(extra) = record.fields.get(header.len()) {
+                // Points to the delimiter before the extra field.
+                let offset = extra.offset - self.delimiter.len_utf8();
+                return Err(Error::new("CSV", s, offset, &["line break"]));
+            }
+            let map = header
+                .iter()
+                .zip(record.fields)
+                .map(|(k, v)| (k.text.clone(), self.value(v.text)))
+                .collect::<Map>();
+            values.push(Value::Map(map));
+        }
+        Ok(Value::Array(values))
+    }
+}
+
+impl CsvParser {
+    /// Splits the input into records, skipping blank lines.
+    fn records(&self, s: &str) -> Result<Vec<Record>, Error> {
+        let mut records = Vec::new();
+        let mut fields = Vec::new();
+        let mut chars = s.char_indices().peekable();
+        // Excel writes a BOM at the head of UTF-8 CSV.
+        chars.next_if(|(_, c)| *c == '\u{feff}');
+        loop {
+            let offset = chars.peek().map_or(s.len(), |(i, _)| *i);
+            let mut text = String::new();
+            let quoted = chars.next_if(|(_, c)| *c == '"').is_some();
+            if quoted {
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) if chars.next_if(|(_, c)| *c == '"').is_none() => break,
+                        Some((_, c)) => text.push(c),
+                        None => return Err(Error::new("CSV", s, s.len(), &["\"\\\"\""])),
+                    }
+                }
+            } else {
+                while let Some((_, c)) =
+                    chars.next_if(|(_, c)| *c != self.delimiter && *c != '\n' && *c != '\r')
+                {
+                    text.push(c);
+                }
+            }
+            let blank = fields.is_empty() && !quoted && text.is_empty();
+            fields.push(Field { offset, text });
+
+            let end = chars.peek().map_or(s.len(), |(i, _)| *i);
+            match chars.next() {
+                Some((_, c)) if c == self.delimiter => continue,
+                Some((_, '\n')) => {}
+                Some((_, '\r')) if chars.next_if(|(_, c)| *c == '\n').is_some() => {}
+                None => {}
+                Some(_) if quoted => {
+                    return Err(Error::new("CSV", s, end, &["delimiter", "line break"]))
+                }
+                // A lone CR, which must be quoted.
+                Some(_) => return Err(Error::new("CSV", s, end, &["\"\\r\\n\""])),
+            }
+            let fields = std::mem::take(&mut fields);
+            if !blank {
+                records.push(Record { fields, end });
+            }
+            if chars.peek().is_none() {
+                return Ok(records);
+            }
+        }
+    }
+
+    fn value(&self, text: String) -> Value {
+        if !self.infer_types {
+            return Value::String(text);
+        }
+        match text.as_str() {
+            "" => Value::Null,
+            "true" | "True" | "TRUE" => Value::Boolean(true),
+            "false" | "False" | "FALSE" => Value::Boolean(false),
+            t if is_number(t) => match t.parse() {
+                Ok(i) if !t.contains(&['.', 'e', 'E'][..]) => Value::Int(i),
+                _ => t.parse().map_or(Value::String(text), Value::Float),
+            },
+            _ => Value::String(text),
+        }
+    }
+}
+
+/// Whether the text is a number of JSON, which excludes leading zeros like
+/// `007`.
+fn is_number(text: &str) -> bool {
+    let digits = |s: &str| s.len() - s.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let rest = text.strip_prefix('-').unwrap_or(text);
+    let int = digits(rest);
+    if int == 0 || int > 1 && rest.starts_with('0') {
+        return false;
+    }
+    let mut rest = &rest[int..];
+    if let Some(frac) = rest.strip_prefix('.') {
+        if digits(frac) == 0 {
+            return false;
+        }
+        rest = &frac[digits(frac)..];
+    }
+    if let Some(exp) = rest.strip_prefix(&['e', 'E'][..]) {
+        let exp = exp.strip_prefix(&['+', '-'][..]).unwrap_or(exp);
+        if digits(exp) == 0 {
+            return false;
+        }
+        rest = &exp[digits(exp)..];
+    }
+    rest.is_empty()
+}
@@ -0,0 +1,159 @@
+//! Dotenv Parser
+use crate::{Error, Map, Parser, Value};
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+/// Dotenv (`.env`) Parser, which reads variables as a map of strings.
+///
+/// Lines are like `KEY=value`, optionally prefixed by `export`. Single quoted
+/// values are read literally, and double quoted ones may have escapes such as
+/// `\n` and `\"`. Both may span lines. Unquoted values are trimmed, and end at
+/// ` #` comments. Variables are not expanded, and later lines win.
+#[derive(Copy, Clone, Debug)]
+pub struct DotenvParser;
+
+/// Whether the character can be in names, which don't start with digits,
+/// `.` or `-`.
+fn is_name(c: char, head: bool) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || !head && (c.is_ascii_digit() || c == '.' || c == '-')
+}
+
+struct Reader<'a> {
+    input: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl Reader<'_> {
+    fn offset(&mut self) -> usize {
+        let len = self.input.len();
+        self.chars.peek().map_or(len, |(i, _)| *i)
+    }
+
+    fn error(&mut self, expected: &[&'static str]) -> Error {
+        Error::new("dotenv", self.input, self.offset(), expected)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.chars.next_if(|(_, d)| *d == c).is_some()
+    }
+
+    fn skip_spaces(&mut self) {
+        while self
+            .chars
+            .next_if(|(_, c)| *c == ' ' || *c == '\t')
+            .is_some()
+        {}
+    }
+
+    /// Skips a comment if any and the line break, or reports the rest.
+    fn end_line(&mut self) -> Result<(), Error> {
+        self.skip_spaces();
+        if self.eat('#') {
+            while self.chars.next_if(|(_, c)| *c != '\n').is_some() {}
+        }
+        self.eat('\r');
+        if self.eat('\n') || self.chars.peek().is_none() {
+            Ok(())
+        } else {
+            Err(self.error(&["\"#\"", "line break"]))
+        }
+    }
+
+    fn name(&mut self) -> Result<String, Error> {
+        let mut name = String::new();
+        while let Some((_, c)) = self.chars.next_if(|(_, c)| is_name(*c, name.is_empty())) {
+            name.push(c);
+        }
+        if name.is_empty() {
+            return Err(self.error(&["name"]));
+        }
+        Ok(name)
+    }
+
+    /// Reads a value, where `spaced` tells whether whitespace precedes it.
+    fn value(&mut self, spaced: bool) -> Result<String, Error> {
+        let mut value = String::new();
+        if self.eat('\'') {
+            while let Some((_, c)) = self.chars.next_if(|(_, c)| *c != '\'') {
+                value.push(c);
+            }
+            if !self.eat('\'') {
+                return Err(self.error(&["\"'\""]));
+            }
+        } else if self.eat('"') {
+            while let Some((_, c)) = self.chars.next_if(|(_, c)| *c != '"') {
+                if c != '\\' {
+                    value.push(c);
+                    continue;
+                }
+                match self.chars.next_if(|(_, c)| *c != '\n').map(|(_, e)| e) {
+                    Some('n') => value.push('\n'),
+                    Some('r') => value.push('\r'),
+                    Some('t') => value.push('\t'),
+                    Some(e @ '\\') | Some(e @ '"') | Some(e @ '$') | Some(e @ '`') => value.push(e),
+                    Some(e) => value.extend(&['\\', e]),
+                    None => value.push('\\'),
+                }
+            }
+            if !self.eat('"') {
+                return Err(self.error(&["\"\\\"\""]));
+            }
+        } else {
+            while let Some((_, c)) = self.chars.next_if(|(_, c)| *c != '\n') {
+                // Comments need preceding whitespace.
+                if c == '#' && (value.is_empty() && spaced || value.ends_with(&[' ', '\t'][..])) {
+                    while self.chars.next_if(|(_, c)| *c != '\n').is_some() {}
+                    break;
+                }
+                value.push(c);
+            }
+            value.truncate(value.trim_end().len());
+        }
+        Ok(value)
+    }
+}
+
+impl Parser for DotenvParser {
+    const FORMAT: &'static str = "dotenv";
+
+    fn parse(&self, s: &str) -> Result<Value, Error> {
+        let mut reader = Reader {
+            input: s,
+            chars: s.char_indices().peekable(),
+        };
+        let mut map = Map::new();
+        loop {
+            reader.skip_spaces();
+            match reader.chars.peek() {
+                None => return Ok(Value::Map(map)),
+                Some((_, '#')) | Some((_, '\r')) | Some((_, '\n')) => {
+                    reader.end_line()?;
+                    continue;
+                }
+                Some(_) => {}
+            }
+            let mut name = reader.name()?;
+            if name == "export"
+                && reader
+                    .chars
+                    .peek()
+                    .is_some_and(|(_, c)| *c == ' ' || *c == '\t')
+            {
+                reader.skip_spaces();
+                name = reader.name()?;
+            }
+            reader.skip_spaces();
+            if !reader.eat('=') {
+                return Err(reader.error(&["\"=\""]));
+            }
+            let spaced = reader
+                .chars
+                .peek()
+                .is_some_and(|(_, c)| *c == ' ' || *c == '\t');
+            reader.skip_spaces();
+            let value = reader.value(spaced)?;
+            reader.end_line()?;
+            map.insert(name, Value::String(value));
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! INI Parser
+use crate::{line, Error, Map, Parser, Value};
+
+/// INI Parser, which reads sections as nested maps of strings.
+///
+/// Keys are separated from values by `=` or `:`, and keys before any section
+/// are put in the root map. Lines starting with `;` or `#` are comments. Values
+/// are trimmed, and section names, keys and values may be double quoted with
+/// escapes of the line format, like `"a\tb"`.
+#[derive(Copy, Clone, Debug)]
+pub struct IniParser;
+
+/// Reads a quoted string or a raw text ending at one of `ends`, trimmed, and
+/// returns it with the rest.
+fn token<'a>(s: &'a str, ends: &[char]) -> Option<(String, &'a str)> {
+    if s.starts_with('"') {
+        let (text, len) = line::unquote(s)?;
+        Some((text, s[len..].trim_start()))
+    } else {
+        let end = s.find(ends).unwrap_or(s.len());
+        Some((s[..end].trim_end().to_string(), &s[end..]))
+    }
+}
+
+/// Whether the rest of the line is blank or a comment.
+fn is_end(s: &str) -> bool {
+    s.is_empty() || s.starts_with(&[';', '#'][..])
+}
+
+impl Parser for IniParser {
+    const FORMAT: &'static str = "INI";
+
+    fn parse(&self, s: &str) -> Result<Value, Error> {
+        let mut root = Map::new();
+        let mut section: Option<String> = None;
+        let mut offset = 0;
+        for text in s.split_inclusive('\n') {
+            let line = text.trim();
+            // The offset of the trimmed line, and of the rest in it.
+            let head = offset + (text.len() - text.trim_start().len());
+            let error = |rest: &str, e| Error::new("INI", s, head + line.len() - rest.len(), e);
+            offset += text.len();
+            if is_end(line) {
+                continue;
+            }
+
+            if let Some(rest) = line.strip_prefix('[') {
+                let (name, rest) =
+                    token(rest.trim_start(), &[']']).ok_or_else(|| error(rest, &["string"]))?;
+                let rest = rest
+                    .strip_prefix(']')
+                    .ok_or_else(|| error(rest, &["\"]\""]))?
+                    .trim_start();
+                if !is_end(rest) {
+                    return Err(error(rest, &["\";\"", "line break"]));
+                }
+                if root.contains_key(&name) {
+                    return Err(error(line, &["unique section"]));
+                }
+                root.insert(name.clone(), Value::Map(Map::new()));
+                section = Some(name);
+                continue;
+            }
+
+            let (key, rest) = token(line, &['=', ':']).ok_or_else(|| error(line, &["string"]))?;
+            let rest = match rest.strip_prefix(&['=', ':'][..]) {
+                Some(rest) if !key.is_empty() || line.starts_with('"') => rest.trim_start(),
+                Some(_) => return Err(error(line, &["key"])),
+                None => return Err(error(rest, &["\"=\"", "\":\""])),
+            };
+            let value = if rest.starts_with('"') {
+                let (value, after) = token(rest, &[]).ok_or_else(|| error(rest, &["string"]))?;
+                if !is_end(after) {
+                    return Err(error(after, &["\";\"", "line break"]));
+                }
+                value
+            } else {
+                rest.to_string()
+            };
+
+            let map = match &section {
+                Some(name) => match root.get_mut(name) {
+                    Some(Value::Map(m)) => m,
+                    _ => unreachable!(),
+                },
+                None => &mut root,
+            };
+            if map.contains_key(&key) {
+                return Err(error(line, &["unique key"]));
+            }
+            map.insert(key, Value::String(value));
+        }
+        Ok(Value::Map(root))
+    }
+}
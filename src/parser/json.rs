@@ -1,7 +1,8 @@
 //! JSON Parser
-use crate::{Parser, Value};
-use std::collections::HashMap;
+use super::{Documents, Events};
+use crate::{Error, Map, Parser, Value};
 use std::convert::TryFrom;
+use std::io;
 use std::iter::FromIterator;
 
 peg::parser! {grammar json_parser() for str {
@@ -22,7 +23,7 @@ peg::parser! {grammar json_parser() for str {
         / null()
 
     rule object() -> Value
-        = "{" _ m:(member() ** ",") _ "}" { Value::Map(HashMap::from_iter(m)) }
+        = "{" _ m:(member() ** ",") _ "}" { Value::Map(Map::from_iter(m)) }
 
     rule member() -> (String, Value)
         = _ s:string_() _ ":" e:elem() { (s, e) }
@@ -56,6 +57,13 @@ peg::parser! {grammar json_parser() for str {
         / "n"  { '\n' }
         / "r"  { '\r' }
         / "t"  { '\t' }
+        / "u" h:$(['d' | 'D'] ['8'..='9' | 'a'..='b' | 'A'..='B'] hex()*<2>)
+          "\\u" l:$(['d' | 'D'] ['c'..='f' | 'C'..='F'] hex()*<2>) {?
+            // A surrogate pair
+            let h = u32::from_str_radix(h, 16).or(Err("hexchar"))?;
+            let l = u32::from_str_radix(l, 16).or(Err("hexchar"))?;
+            char::try_from(0x10000 + ((h - 0xd800) << 10) + (l - 0xdc00)).or(Err("escape"))
+        }
         / "u" h:$(hex()*<4>) {? char::try_from(u32::from_str_radix(h, 16).or(Err("hexchar"))?).or(Err("escape")) }
 
     rule number() -> Value
@@ -96,9 +104,23 @@ peg::parser! {grammar json_parser() for str {
 #[derive(Copy, Clone, Debug)]
 pub struct JsonParser;
 
+impl JsonParser {
+    /// Reads the document from `reader` incrementally, as an iterator of events.
+    pub fn events<R: io::Read>(&self, reader: R) -> Events<R> {
+        Events::new(reader, false)
+    }
+
+    /// Reads multiple documents from `reader` one by one, which are separated by
+    /// whitespace or record separators (RFC 7464), or just concatenated.
+    pub fn documents<R: io::Read>(&self, reader: R) -> Documents<R> {
+        Documents::new(reader, false)
+    }
+}
+
 impl Parser for JsonParser {
-    type Err = peg::error::ParseError<peg::str::LineCol>;
-    fn parse(&self, s: &str) -> Result<Value, Self::Err> {
-        json_parser::json(s)
+    const FORMAT: &'static str = "JSON";
+
+    fn parse(&self, s: &str) -> Result<Value, Error> {
+        json_parser::json(s).map_err(|e| Error::from_peg("JSON", e))
     }
 }
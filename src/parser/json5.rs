@@ -1,7 +1,8 @@
 //! JSON5 Parser
-use crate::{Parser, Value};
-use std::collections::HashMap;
+use super::{Documents, Events};
+use crate::{Error, Map, Parser, Value};
 use std::convert::TryFrom;
+use std::io;
 use std::iter::FromIterator;
 use unicode_categories::UnicodeCategories;
 
@@ -27,7 +28,7 @@ peg::parser! {grammar json5_parser() for str {
         / null()
 
     rule object() -> Value
-        = "{" _ m:(member() ** ",") _ ","? _ "}" { Value::Map(HashMap::from_iter(m)) }
+        = "{" _ m:(member() ** ",") _ ","? _ "}" { Value::Map(Map::from_iter(m)) }
 
     rule member() -> (String, Value)
         = _ s:(ident() / string_()) _ ":" e:elem() { (s, e) }
@@ -43,7 +44,7 @@ peg::parser! {grammar json5_parser() for str {
                     c.is_letter_other()     ||
                     c == '$' || c == '_'
               ]) { c.chars().next().unwrap() }
-        / "u" h:$(hex()*<4>) {? char::try_from(u32::from_str_radix(h, 16).or(Err("hexchar"))?).or(Err("escape")) }
+        / "\\u" h:$(hex()*<4>) {? char::try_from(u32::from_str_radix(h, 16).or(Err("hexchar"))?).or(Err("escape")) }
 
     rule ident_part() -> char
         = ident_start()
@@ -68,21 +69,21 @@ peg::parser! {grammar json5_parser() for str {
         = s:string_() { Value::String(s) }
 
     rule string_() -> String
-        = "\"" s:double_char()* "\"" { String::from_iter(s.into_iter().filter_map(|c| c)) }
-        / "'" s:single_char()* "'" { String::from_iter(s.into_iter().filter_map(|c| c)) }
+        = "\"" s:double_char()* "\"" { String::from_iter(s.into_iter().flatten()) }
+        / "'" s:single_char()* "'" { String::from_iter(s.into_iter().flatten()) }
 
     rule double_char() -> Option<char>
         = c:$([^ '"' | '\\' | '\n' | '\r']) { Some(c.chars().next().unwrap()) }
         / "\\" c:(
             e:escape() { Some(e) }
-            / __ { None }
+            / !['u' | 'x'] __ { None }
         ) { c }
 
     rule single_char() -> Option<char>
         = c:$([^ '\'' | '\\' | '\n' | '\r']) { Some(c.chars().next().unwrap()) }
         / "\\" c:(
             e:escape() { Some(e) }
-            / __ { None }
+            / !['u' | 'x'] __ { None }
         ) { c }
 
     rule escape() -> char
@@ -97,6 +98,13 @@ peg::parser! {grammar json5_parser() for str {
         / "r"  { '\r' }
         / "t"  { '\t' }
         / "x" h:$(hex()*<2>) {? Ok(u8::from_str_radix(h, 16).or(Err("hexchar"))? as char) }
+        / "u" h:$(['d' | 'D'] ['8'..='9' | 'a'..='b' | 'A'..='B'] hex()*<2>)
+          "\\u" l:$(['d' | 'D'] ['c'..='f' | 'C'..='F'] hex()*<2>) {?
+            // A surrogate pair
+            let h = u32::from_str_radix(h, 16).or(Err("hexchar"))?;
+            let l = u32::from_str_radix(l, 16).or(Err("hexchar"))?;
+            char::try_from(0x10000 + ((h - 0xd800) << 10) + (l - 0xdc00)).or(Err("escape"))
+        }
         / "u" h:$(hex()*<4>) {? char::try_from(u32::from_str_radix(h, 16).or(Err("hexchar"))?).or(Err("escape")) }
 
     rule number() -> Value
@@ -138,9 +146,23 @@ peg::parser! {grammar json5_parser() for str {
 #[derive(Copy, Clone, Debug)]
 pub struct Json5Parser;
 
+impl Json5Parser {
+    /// Reads the document from `reader` incrementally, as an iterator of events.
+    pub fn events<R: io::Read>(&self, reader: R) -> Events<R> {
+        Events::new(reader, true)
+    }
+
+    /// Reads multiple documents from `reader` one by one, which are separated by
+    /// whitespace or record separators (RFC 7464), or just concatenated.
+    pub fn documents<R: io::Read>(&self, reader: R) -> Documents<R> {
+        Documents::new(reader, true)
+    }
+}
+
 impl Parser for Json5Parser {
-    type Err = peg::error::ParseError<peg::str::LineCol>;
-    fn parse(&self, s: &str) -> Result<Value, Self::Err> {
-        json5_parser::json5(s)
+    const FORMAT: &'static str = "JSON5";
+
+    fn parse(&self, s: &str) -> Result<Value, Error> {
+        json5_parser::json5(s).map_err(|e| Error::from_peg("JSON5", e))
     }
 }
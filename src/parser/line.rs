@@ -1,7 +1,7 @@
 //! Line Parser
 
-use crate::{Parser, Value};
 use crate::datetime::*;
+use crate::{line, Error, Map, Parser, Value};
 use std::collections::HashMap;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -9,6 +9,8 @@ pub struct LineParser {
     root: String,
     delimiter: String,
     equal: String,
+    bracket_paths: bool,
+    lenient: bool,
 }
 
 impl Default for LineParser {
@@ -17,6 +19,8 @@ impl Default for LineParser {
             root: "$".to_string(),
             delimiter: ".".to_string(),
             equal: " = ".to_string(),
+            bracket_paths: false,
+            lenient: false,
         }
     }
 }
@@ -40,9 +44,22 @@ impl LineParser {
         self.equal = equal.into();
         self
     }
+
+    /// Also accepts paths like `$.users[0]["first.name"]`.
+    pub fn bracket_paths(&mut self, bracket_paths: bool) -> &mut Self {
+        self.bracket_paths = bracket_paths;
+        self
+    }
+
+    /// Fills gaps of array indices with nulls and lets later lines win,
+    /// instead of reporting conflicting lines as errors.
+    pub fn lenient(&mut self, lenient: bool) -> &mut Self {
+        self.lenient = lenient;
+        self
+    }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 enum Path {
     Array(usize),
     Map(String),
@@ -50,10 +67,43 @@ enum Path {
 
 #[derive(Clone, Debug)]
 pub struct Item {
-    path: Vec<Path>,
+    offset: usize,
+    path: Vec<(usize, Path)>,
     value: Value,
 }
 
+/// The value being built from lines, remembering the offsets of the lines
+/// which made each node, to point to them on conflicts.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Tree {
+    root: Option<Value>,
+    origins: HashMap<Vec<Path>, usize>,
+}
+
+impl Tree {
+    pub(crate) fn into_value(self) -> Option<Value> {
+        self.root
+    }
+}
+
+/// An error of inserting a line, which may conflict with an earlier line.
+#[derive(Clone, Debug)]
+pub(crate) struct Conflict {
+    offset: usize,
+    expected: &'static str,
+    previous: Option<usize>,
+}
+
+impl Conflict {
+    pub(crate) fn error(&self, format: &'static str, input: &str) -> Error {
+        let error = Error::new(format, input, self.offset, &[self.expected]);
+        match self.previous {
+            Some(previous) => error.conflicting(input, previous),
+            None => error,
+        }
+    }
+}
+
 peg::parser! {grammar printer() for str {
     use peg::ParseLiteral;
     use std::iter::FromIterator;
@@ -62,13 +112,19 @@ peg::parser! {grammar printer() for str {
     pub rule printer(config: &LineParser) -> Vec<Item>
         = i:(item(config)**"\n") "\n"? { i }
 
+    /// A path without the root, like keys of properties.
+    pub rule relative_path(config: &LineParser) -> Vec<(usize, Path)>
+        = p:position!() h:path(config) t:(p:position!() s:segment(config) { (p, s) })* {
+            std::iter::once((p, h)).chain(t).collect()
+        }
+
     rule item(config: &LineParser) -> Item
-        = root(config)
-          delim(config)
-          path:(path(config)**delim(config))
+        = offset:position!()
+          root(config)
+          path:(p:position!() s:segment(config) { (p, s) })*
           equal(config)
           value:value()
-          { Item { path, value } }
+          { Item { offset, path, value } }
 
     rule root(config: &LineParser)
         = ##parse_string_literal(&config.root)
@@ -79,15 +135,28 @@ peg::parser! {grammar printer() for str {
     rule equal(config: &LineParser)
         = ##parse_string_literal(&config.equal)
 
+    rule bracket(config: &LineParser)
+        = quiet!{ {? if config.bracket_paths { Ok(()) } else { Err("bracket") } } } "["
+
+    rule segment(config: &LineParser) -> Path
+        = delim(config) p:path(config) { p }
+        / bracket(config) p:(
+            i:array() { Path::Array(i) }
+            / s:string() { Path::Map(s) }
+          ) "]" { p }
+
     rule path(config: &LineParser) -> Path
-        = i:array() { Path::Array(i) }
+        = s:string() { Path::Map(s) }
+        / i:array() &(delim(config) / equal(config) / bracket(config) / ![_]) { Path::Array(i) }
         / s:map(config) { Path::Map(s) }
 
     rule array() -> usize
         = i:$(['1'..='9']['0'..='9']* / "0") {? i.parse().or(Err("array")) }
 
     rule map(config: &LineParser) -> String
-        = s:$((!delim(config) !equal(config) [_])+) { s.to_string() }
+        = !"\"" s:$((!delim(config) !equal(config) !bracket(config) [^ '\n'])+) {
+            s.to_string()
+        }
 
     rule value() -> Value
         = "null"       { Value::Null }
@@ -97,7 +166,7 @@ peg::parser! {grammar printer() for str {
         / "inf"        { Value::Float(f64::INFINITY) }
         / "-inf"       { Value::Float(f64::NEG_INFINITY) }
         / "[]"         { Value::Array(Vec::new()) }
-        / "{}"         { Value::Map(HashMap::new()) }
+        / "{}"         { Value::Map(Map::new()) }
         / d:datetime() { Value::DateTime(d) }
         / n:number()   { n }
         / s:string()   { Value::String(s) }
@@ -110,33 +179,25 @@ peg::parser! {grammar printer() for str {
         / "\\" e:escape() { e }
 
     rule escape() -> char
-        = "\"" { '"' }
-        / "'"  { '\'' }
-        / "\\" { '\\' }
-        / "0"  { '\0' }
-        / "n"  { '\n' }
-        / "r"  { '\r' }
-        / "t"  { '\t' }
+        = "'" { '\'' } // Never generated, but accepted as Rust does.
         / "u{" h:hex() "}" {?
             char::try_from(
                 u32::from_str_radix(h, 16).or(Err("hex"))?
             ).or(Err("escape"))
         }
+        / c:$([_]) {? line::unescape(c.chars().next().unwrap()).ok_or("escape") }
 
     rule hex() -> &'input str
         = $(['0'..='9' | 'a'..='f' | 'A'..='F']+)
 
-    // rule number() -> Value
-    //     = d:digits() f:fraction()? {?
-    //         Ok(if let Some(f) = f {
-    //             Value::Float([d, f].concat().parse().or(Err("float"))?)
-    //         } else {
-    //             Value::Int(d.parse().or(Err("int"))?)
-    //         })
-    //     }
-
     rule number() -> Value
-        = d:digits() {? Ok(Value::Int(d.parse().or(Err("int"))?)) }
+        = d:digits() f:$(fraction()? exponent()?) {?
+            Ok(if f.is_empty() {
+                Value::Int(d.parse().or(Err("int"))?)
+            } else {
+                Value::Float([d, f].concat().parse().or(Err("float"))?)
+            })
+        }
 
     rule digits() -> &'input str
         = $(("-"/"") (['1'..='9']['0'..='9']* / "0"))
@@ -144,6 +205,9 @@ peg::parser! {grammar printer() for str {
     rule fraction() -> &'input str
         = $("." ['0'..='9']+)
 
+    rule exponent() -> &'input str
+        = $(['e' | 'E'] ("+" / "-")? ['0'..='9']+)
+
     rule datetime() -> DateTime
         = d:date() t:(("T" / " ") t:time() { t })? o:offset()? {?
             DateTime::new(Some(d), t, o).or(Err("datetime"))
@@ -180,57 +244,138 @@ peg::parser! {grammar printer() for str {
         }
 }}
 
-impl Value {
-    fn append_item(&mut self, item: &mut Item) {
-        if item.path.is_empty() {
-            *self = item.value.clone();
-            return;
+impl LineParser {
+    /// Sets the value at `key`, a path without the root, into the tree, where
+    /// errors point to `offset`, as the key may be unescaped from the input.
+    pub(crate) fn insert_key(
+        &self,
+        tree: &mut Tree,
+        key: &str,
+        offset: usize,
+        value: Value,
+    ) -> Result<(), Conflict> {
+        let path = printer::relative_path(key, self).map_err(|_| Conflict {
+            offset,
+            expected: "path",
+            previous: None,
+        })?;
+        let path = path.into_iter().map(|(_, p)| (offset, p)).collect();
+        self.insert(
+            tree,
+            Item {
+                offset,
+                path,
+                value,
+            },
+        )
+    }
+
+    /// Sets the value of the item into the tree.
+    fn insert(&self, tree: &mut Tree, item: Item) -> Result<(), Conflict> {
+        let Tree { root, origins } = tree;
+        let mut new = root.is_none();
+        let mut node = root.get_or_insert(Value::Null);
+        let mut prefix = Vec::new();
+        if new {
+            origins.insert(Vec::new(), item.offset);
+        }
+        for (offset, segment) in &item.path {
+            // Nodes which aren't new have been made by earlier lines.
+            let origin = origins.get(&prefix).copied();
+            let (child, created) = self.child(node, new, *offset, segment, origin)?;
+            prefix.push(segment.clone());
+            if created {
+                origins.insert(prefix.clone(), item.offset);
+            }
+            node = child;
+            new = created;
         }
 
-        let path = item.path.drain(0..1).next().unwrap();
-        let child = match path {
-            Path::Array(i) => {
-                if !matches!(self, Value::Array(_)) {
-                    *self = Value::Array(Vec::new());
-                }
-                if let Value::Array(v) = self {
-                    if v.len() == i {
-                        v.insert(i, Value::Null);
-                    }
-                    &mut v[i]
-                } else {
-                    unreachable!()
-                }
+        if !new && !self.lenient {
+            return Err(Conflict {
+                offset: item.offset,
+                expected: "unique path",
+                previous: origins.get(&prefix).copied(),
+            });
+        }
+        *node = item.value;
+        Ok(())
+    }
+
+    /// Returns the child of the node at the segment, and whether it is made
+    /// now. `origin` is the offset of the line which made the node.
+    fn child<'a>(
+        &self,
+        node: &'a mut Value,
+        new: bool,
+        offset: usize,
+        segment: &Path,
+        origin: Option<usize>,
+    ) -> Result<(&'a mut Value, bool), Conflict> {
+        let conflict = match (&*node, segment) {
+            (Value::Array(_), Path::Array(_)) | (Value::Map(_), Path::Map(_)) => None,
+            (Value::Array(_), Path::Map(_)) => Some("array index"),
+            (Value::Map(_), Path::Array(_)) => Some("map key"),
+            // Scalars can't have children.
+            _ => Some("unique path"),
+        };
+        match conflict {
+            Some(expected) if !new && !self.lenient => {
+                return Err(Conflict {
+                    offset,
+                    expected,
+                    previous: origin,
+                })
             }
-            Path::Map(k) => {
-                if !matches!(self, Value::Map(_)) {
-                    *self = Value::Map(HashMap::new());
+            Some(_) => {
+                *node = match segment {
+                    Path::Array(_) => Value::Array(Vec::new()),
+                    Path::Map(_) => Value::Map(Map::new()),
                 }
-                if let Value::Map(m) = self {
-                    if !m.contains_key(&k) {
-                        m.insert(k.clone(), Value::Null);
+            }
+            None => {}
+        }
+
+        match (node, segment) {
+            (Value::Array(v), Path::Array(i)) => {
+                let i = *i;
+                if i > v.len() {
+                    if !self.lenient {
+                        return Err(Conflict {
+                            offset,
+                            expected: "consecutive index",
+                            previous: None,
+                        });
                     }
-                    m.get_mut(&k).unwrap()
-                } else {
-                    unreachable!()
+                    v.resize(i, Value::Null);
                 }
+                let created = i == v.len();
+                if created {
+                    v.push(Value::Null);
+                }
+                Ok((&mut v[i], created))
             }
-        };
-
-        child.append_item(item)
+            (Value::Map(m), Path::Map(k)) => {
+                let created = !m.contains_key(k);
+                Ok((m.entry(k.clone()).or_insert(Value::Null), created))
+            }
+            _ => unreachable!(),
+        }
     }
 }
 
 impl Parser for LineParser {
-    type Err = peg::error::ParseError<peg::str::LineCol>;
-    fn parse(&self, s: &str) -> Result<Value, Self::Err> {
-        let mut items = printer::printer(s, self)?;
+    const FORMAT: &'static str = "line format";
+
+    fn parse(&self, s: &str) -> Result<Value, Error> {
+        let items = printer::printer(s, self).map_err(|e| Error::from_peg("line format", e))?;
 
-        let mut value = Value::Null;
-        for i in items.iter_mut() {
-            value.append_item(i);
+        let mut tree = Tree::default();
+        for item in items {
+            self.insert(&mut tree, item)
+                .map_err(|e| e.error("line format", s))?;
         }
 
-        Ok(value)
+        Ok(tree.into_value().unwrap_or(Value::Null))
     }
 }
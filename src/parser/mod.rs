@@ -1,30 +1,54 @@
 //! Parser Implementations
+mod csv;
+mod dotenv;
+mod ini;
 mod json;
 mod json5;
 mod line;
+mod msgpack;
+mod ndjson;
+mod properties;
+mod stream;
 mod toml;
+mod xml;
+mod yaml;
 
+pub use csv::CsvParser;
+pub use dotenv::DotenvParser;
+pub use ini::IniParser;
 pub use json::JsonParser;
 pub use json5::Json5Parser;
 pub use line::LineParser;
+pub use msgpack::MsgpackParser;
+pub use ndjson::{NdjsonParser, Records};
+pub use properties::PropertiesParser;
+pub use stream::{Documents, Event, Events};
 pub use toml::TomlParser;
+pub use xml::XmlParser;
+pub use yaml::YamlParser;
 
-use crate::Value;
-use std::error::Error;
+use crate::{Error, Value};
 use std::fmt;
 use std::io;
 use std::path::Path;
 
 /// The error type for "read_from" function.
 #[derive(Debug)]
-pub enum CombinedError<E> {
+pub enum CombinedError {
     Io(io::Error),
-    Parse(E),
+    Parse(Error),
 }
 
-impl<E: Error> Error for CombinedError<E> {}
+impl std::error::Error for CombinedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Parse(e) => Some(e),
+        }
+    }
+}
 
-impl<E: Error> fmt::Display for CombinedError<E> {
+impl fmt::Display for CombinedError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Self::Io(e) => write!(f, "IO Error: {}", e),
@@ -33,7 +57,7 @@ impl<E: Error> fmt::Display for CombinedError<E> {
     }
 }
 
-impl<E: Error> From<io::Error> for CombinedError<E> {
+impl From<io::Error> for CombinedError {
     fn from(e: io::Error) -> Self {
         Self::Io(e)
     }
@@ -41,20 +65,30 @@ impl<E: Error> From<io::Error> for CombinedError<E> {
 
 /// The unified interface for parsing data format.
 pub trait Parser {
-    type Err: Error;
-    fn parse(&self, s: &str) -> Result<Value, Self::Err>;
+    /// The name of the format, shown in errors.
+    const FORMAT: &'static str;
+
+    fn parse(&self, s: &str) -> Result<Value, Error>;
+
+    /// Parses raw bytes, which text formats require to be UTF-8.
+    fn parse_bytes(&self, bytes: &[u8]) -> Result<Value, Error> {
+        match std::str::from_utf8(bytes) {
+            Ok(s) => self.parse(s),
+            Err(e) => {
+                let valid = std::str::from_utf8(&bytes[..e.valid_up_to()]).unwrap();
+                Err(Error::new(Self::FORMAT, valid, valid.len(), &["UTF-8"]))
+            }
+        }
+    }
 
-    fn read_from<R: io::Read>(&self, buf: &mut R) -> Result<Value, CombinedError<Self::Err>> {
-        let mut s = String::new();
-        buf.read_to_string(&mut s)?;
-        self.parse(&s).map_err(CombinedError::Parse)
+    fn read_from<R: io::Read>(&self, buf: &mut R) -> Result<Value, CombinedError> {
+        let mut bytes = Vec::new();
+        buf.read_to_end(&mut bytes)?;
+        self.parse_bytes(&bytes).map_err(CombinedError::Parse)
     }
 
     #[cfg(feature = "bin")]
-    fn read_path<P: AsRef<Path>>(
-        &self,
-        path: Option<P>,
-    ) -> Result<Value, CombinedError<Self::Err>> {
+    fn read_path<P: AsRef<Path>>(&self, path: Option<P>) -> Result<Value, CombinedError> {
         self.read_from(&mut crate::io::Input::from_path(path)?)
     }
 }
@@ -0,0 +1,192 @@
+//! MessagePack Parser
+use crate::datetime::DateTime;
+use crate::{Error, Map, Parser, Value};
+use std::convert::TryFrom;
+
+/// MessagePack Parser, which reads a single value from bytes by `parse_bytes`.
+///
+/// Binary data is read as an array of byte integers, and integer keys of maps
+/// as strings. The timestamp extension is read as a date-time in UTC, and the
+/// other extensions as maps of the `type` integer and the `data` bytes.
+#[derive(Copy, Clone, Debug)]
+pub struct MsgpackParser;
+
+/// The limit of nested arrays and maps, not to overflow the stack.
+const DEPTH_LIMIT: usize = 128;
+
+impl Parser for MsgpackParser {
+    const FORMAT: &'static str = "MessagePack";
+
+    /// Parses the bytes of the string.
+    fn parse(&self, s: &str) -> Result<Value, Error> {
+        self.parse_bytes(s.as_bytes())
+    }
+
+    fn parse_bytes(&self, bytes: &[u8]) -> Result<Value, Error> {
+        let mut reader = Reader { bytes, offset: 0 };
+        let value = reader.value(0)?;
+        if reader.offset < bytes.len() {
+            return Err(error(reader.offset, &["EOF"]));
+        }
+        Ok(value)
+    }
+}
+
+fn error(offset: usize, expected: &[&'static str]) -> Error {
+    Error::binary("MessagePack", offset, expected)
+}
+
+/// The bytes being read from `offset`.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// Reads the next `len` bytes.
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        let rest = &self.bytes[self.offset..];
+        if rest.len() < len {
+            return Err(error(self.bytes.len(), &["more bytes"]));
+        }
+        self.offset += len;
+        Ok(&rest[..len])
+    }
+
+    /// Reads a big-endian unsigned integer of `size` bytes.
+    fn uint(&mut self, size: usize) -> Result<u64, Error> {
+        let bytes = self.take(size)?;
+        Ok(bytes.iter().fold(0, |n, &b| n << 8 | u64::from(b)))
+    }
+
+    /// Reads a big-endian signed integer of `size` bytes.
+    fn int(&mut self, size: usize) -> Result<i64, Error> {
+        let shift = 64 - size * 8;
+        Ok((self.uint(size)? << shift) as i64 >> shift)
+    }
+
+    /// Reads a length of `size` bytes, saturated not to overflow `usize`.
+    fn len(&mut self, size: usize) -> Result<usize, Error> {
+        Ok(usize::try_from(self.uint(size)?).unwrap_or(usize::MAX))
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, Error> {
+        let start = self.offset;
+        let marker = self.take(1)?[0];
+        Ok(match marker {
+            0x00..=0x7f => Value::Int(marker.into()),
+            0x80..=0x8f => self.map(start, usize::from(marker & 0x0f), depth)?,
+            0x90..=0x9f => self.array(start, usize::from(marker & 0x0f), depth)?,
+            0xa0..=0xbf => self.string(usize::from(marker & 0x1f))?,
+            0xc0 => Value::Null,
+            0xc1 => return Err(error(start, &[])),
+            0xc2 => Value::Boolean(false),
+            0xc3 => Value::Boolean(true),
+            0xc4..=0xc6 => {
+                let len = self.len(1 << (marker - 0xc4))?;
+                let bytes = self.take(len)?;
+                Value::Array(bytes.iter().map(|&b| Value::Int(b.into())).collect())
+            }
+            0xc7..=0xc9 => {
+                let len = self.len(1 << (marker - 0xc7))?;
+                self.ext(start, len)?
+            }
+            0xca => Value::Float(f32::from_bits(self.uint(4)? as u32).into()),
+            0xcb => Value::Float(f64::from_bits(self.uint(8)?)),
+            0xcc..=0xcf => Value::Int(self.uint(1 << (marker - 0xcc))?.into()),
+            0xd0..=0xd3 => Value::Int(self.int(1 << (marker - 0xd0))?.into()),
+            0xd4..=0xd8 => self.ext(start, 1 << (marker - 0xd4))?,
+            0xd9..=0xdb => {
+                let len = self.len(1 << (marker - 0xd9))?;
+                self.string(len)?
+            }
+            0xdc | 0xdd => {
+                let len = self.len(2 << (marker - 0xdc))?;
+                self.array(start, len, depth)?
+            }
+            0xde | 0xdf => {
+                let len = self.len(2 << (marker - 0xde))?;
+                self.map(start, len, depth)?
+            }
+            0xe0..=0xff => Value::Int((marker as i8).into()),
+        })
+    }
+
+    fn string(&mut self, len: usize) -> Result<Value, Error> {
+        let start = self.offset;
+        let bytes = self.take(len)?;
+        match std::str::from_utf8(bytes) {
+            Ok(s) => Ok(Value::String(s.to_string())),
+            Err(e) => Err(error(start + e.valid_up_to(), &["UTF-8"])),
+        }
+    }
+
+    /// The number of elements to allocate for, where each of them is at least
+    /// a byte long.
+    fn capacity(&self, len: usize) -> usize {
+        len.min(self.bytes.len() - self.offset)
+    }
+
+    fn array(&mut self, start: usize, len: usize, depth: usize) -> Result<Value, Error> {
+        if depth >= DEPTH_LIMIT {
+            return Err(error(start, &["nesting within limits"]));
+        }
+        let mut values = Vec::with_capacity(self.capacity(len));
+        for _ in 0..len {
+            values.push(self.value(depth + 1)?);
+        }
+        Ok(Value::Array(values))
+    }
+
+    fn map(&mut self, start: usize, len: usize, depth: usize) -> Result<Value, Error> {
+        if depth >= DEPTH_LIMIT {
+            return Err(error(start, &["nesting within limits"]));
+        }
+        let mut map = Map::with_capacity(self.capacity(len) / 2);
+        for _ in 0..len {
+            let offset = self.offset;
+            let key = match self.value(depth + 1)? {
+                Value::String(s) => s,
+                Value::Int(i) => i.to_string(),
+                _ => return Err(error(offset, &["string", "integer"])),
+            };
+            if map.contains_key(&key) {
+                return Err(error(offset, &["unique key"]));
+            }
+            let value = self.value(depth + 1)?;
+            map.insert(key, value);
+        }
+        Ok(Value::Map(map))
+    }
+
+    /// Reads an extension of `len` bytes. Timestamps must be of 32, 64 or 96
+    /// bits.
+    fn ext(&mut self, start: usize, len: usize) -> Result<Value, Error> {
+        let ext_type = self.int(1)?;
+        if ext_type != -1 {
+            let data = self.take(len)?;
+            let mut map = Map::with_capacity(2);
+            map.insert("type".to_string(), Value::Int(ext_type.into()));
+            map.insert(
+                "data".to_string(),
+                Value::Array(data.iter().map(|&b| Value::Int(b.into())).collect()),
+            );
+            return Ok(Value::Map(map));
+        }
+        let (seconds, nanosecond) = match len {
+            4 => (self.uint(4)? as i64, 0),
+            8 => {
+                let n = self.uint(8)?;
+                ((n & ((1 << 34) - 1)) as i64, (n >> 34) as u32)
+            }
+            12 => {
+                let nanosecond = self.uint(4)? as u32;
+                (self.int(8)?, nanosecond)
+            }
+            _ => return Err(error(start, &["timestamp"])),
+        };
+        DateTime::from_timestamp(seconds, nanosecond)
+            .map(Value::DateTime)
+            .map_err(|()| error(start, &["timestamp"]))
+    }
+}
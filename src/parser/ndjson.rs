@@ -0,0 +1,94 @@
+//! NDJSON (JSON Lines) Parser
+use super::{CombinedError, JsonParser};
+use crate::{Error, Parser, Value};
+use std::io::{self, BufRead};
+
+/// NDJSON (JSON Lines) Parser, which reads a JSON value from each line.
+///
+/// `parse` returns an array of the values, and `records` reads them one by one.
+/// Blank lines are ignored.
+#[derive(Copy, Clone, Debug)]
+pub struct NdjsonParser;
+
+impl NdjsonParser {
+    /// Reads values from `reader` incrementally, line by line.
+    pub fn records<R: io::Read>(&self, reader: R) -> Records<R> {
+        Records {
+            reader: io::BufReader::new(reader),
+            line: 0,
+            offset: 0,
+        }
+    }
+}
+
+/// Parses a line at `line` and `offset` of the input, ignoring blank ones.
+fn record(text: &str, line: usize, offset: usize) -> Option<Result<Value, Error>> {
+    // Not to point the next line on errors at the end.
+    let text = text.strip_suffix('\n').unwrap_or(text);
+    if text.trim_matches(&[' ', '\t', '\r'][..]).is_empty() {
+        return None;
+    }
+    Some(
+        JsonParser
+            .parse(text)
+            .map_err(|e| e.within(line, offset).in_format(NdjsonParser::FORMAT)),
+    )
+}
+
+impl Parser for NdjsonParser {
+    const FORMAT: &'static str = "NDJSON";
+
+    fn parse(&self, s: &str) -> Result<Value, Error> {
+        let mut values = Vec::new();
+        let mut offset = 0;
+        for (i, text) in s.split_inclusive('\n').enumerate() {
+            if let Some(value) = record(text, i + 1, offset) {
+                values.push(value?);
+            }
+            offset += text.len();
+        }
+        Ok(Value::Array(values))
+    }
+}
+
+/// The iterator over values of lines read from `R`, made by
+/// `NdjsonParser::records`.
+#[derive(Debug)]
+pub struct Records<R> {
+    reader: io::BufReader<R>,
+    /// The number of lines read.
+    line: usize,
+    /// The number of bytes read.
+    offset: usize,
+}
+
+impl<R: io::Read> Iterator for Records<R> {
+    type Item = Result<Value, CombinedError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut bytes = Vec::new();
+        loop {
+            bytes.clear();
+            match self.reader.read_until(b'\n', &mut bytes) {
+                Ok(0) => return None,
+                Ok(n) => {
+                    self.line += 1;
+                    self.offset += n;
+                }
+                Err(e) => return Some(Err(e.into())),
+            }
+            let offset = self.offset - bytes.len();
+            let text = match std::str::from_utf8(&bytes) {
+                Ok(text) => text,
+                Err(e) => {
+                    let valid = std::str::from_utf8(&bytes[..e.valid_up_to()]).unwrap();
+                    let e = Error::new(NdjsonParser::FORMAT, valid, valid.len(), &["UTF-8"]);
+                    return Some(Err(CombinedError::Parse(e.within(self.line, offset))));
+                }
+            };
+            if let Some(value) = record(text, self.line, offset) {
+                return Some(value.map_err(CombinedError::Parse));
+            }
+        }
+    }
+}
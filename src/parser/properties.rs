@@ -0,0 +1,176 @@
+//! Java Properties Parser
+use super::line::Tree;
+use super::LineParser;
+use crate::{Error, Map, Parser, Value};
+
+/// Java `.properties` Parser, which reads keys and values as strings.
+///
+/// With `expand_keys`, keys like `server.hosts.0` are read as paths of the line
+/// format and expanded into nested maps and arrays, where conflicting keys are
+/// errors unless `lenient` is set. Otherwise later keys win as Java does.
+#[derive(Clone, Debug, Default)]
+pub struct PropertiesParser {
+    expand_keys: bool,
+    lenient: bool,
+}
+
+impl PropertiesParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn expand_keys(&mut self, expand_keys: bool) -> &mut Self {
+        self.expand_keys = expand_keys;
+        self
+    }
+
+    /// Lets later keys win over conflicting ones on expanding keys, filling
+    /// gaps of array indices by null.
+    pub fn lenient(&mut self, lenient: bool) -> &mut Self {
+        self.lenient = lenient;
+        self
+    }
+}
+
+/// Whitespace of properties, which doesn't include line breaks.
+fn is_space(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\u{c}')
+}
+
+impl Parser for PropertiesParser {
+    const FORMAT: &'static str = "properties";
+
+    fn parse(&self, s: &str) -> Result<Value, Error> {
+        // Keys are paths without the root, where only line breaks can't be
+        // unquoted.
+        let mut paths = LineParser::new();
+        paths.root("").equal("\n").lenient(self.lenient);
+        let mut flat = Map::new();
+        let mut tree = Tree::default();
+        for line in logical_lines(s) {
+            let offset = line[0].0;
+            let (key, value) =
+                entry(&line).map_err(|i| Error::new("properties", s, i, &["escape"]))?;
+            if self.expand_keys {
+                paths
+                    .insert_key(&mut tree, &key, offset, Value::String(value))
+                    .map_err(|e| e.error("properties", s))?;
+            } else {
+                flat.insert(key, Value::String(value));
+            }
+        }
+        Ok(tree.into_value().unwrap_or(Value::Map(flat)))
+    }
+}
+
+/// Splits the input into logical lines of characters and their offsets,
+/// joining lines ending with a backslash and skipping comments.
+fn logical_lines(s: &str) -> Vec<Vec<(usize, char)>> {
+    let mut natural = Vec::new();
+    let mut offset = 0;
+    for text in s.split_inclusive('\n') {
+        let body = text.trim_end_matches('\n');
+        let body = body.strip_suffix('\r').unwrap_or(body);
+        // Also splits by lone CRs.
+        let mut start = offset;
+        for part in body.split('\r') {
+            natural.push(
+                part.char_indices()
+                    .map(|(i, c)| (start + i, c))
+                    .collect::<Vec<_>>(),
+            );
+            start += part.len() + 1;
+        }
+        offset += text.len();
+    }
+
+    let mut lines = Vec::new();
+    let mut natural = natural.into_iter().map(|line| {
+        let head = line.iter().take_while(|(_, c)| is_space(*c)).count();
+        line[head..].to_vec()
+    });
+    while let Some(mut line) = natural.next() {
+        match line.first() {
+            None | Some((_, '#')) | Some((_, '!')) => continue,
+            Some(_) => {}
+        }
+        loop {
+            let backslashes = line.iter().rev().take_while(|(_, c)| *c == '\\').count();
+            if backslashes % 2 == 0 {
+                break;
+            }
+            line.pop();
+            match natural.next() {
+                Some(next) => line.extend(next),
+                None => break,
+            }
+        }
+        // Lines may be empty after joining blank ones.
+        if !line.is_empty() {
+            lines.push(line);
+        }
+    }
+    lines
+}
+
+/// Reads the key and the value of a logical line, or the offset of an invalid
+/// escape.
+fn entry(line: &[(usize, char)]) -> Result<(String, String), usize> {
+    let mut end = 0;
+    while let Some((_, c)) = line.get(end) {
+        match c {
+            '\\' => end += 2,
+            c if *c == '=' || *c == ':' || is_space(*c) => break,
+            _ => end += 1,
+        }
+    }
+    let end = end.min(line.len());
+    let mut rest = &line[end..];
+    while rest.first().is_some_and(|(_, c)| is_space(*c)) {
+        rest = &rest[1..];
+    }
+    if rest.first().is_some_and(|(_, c)| *c == '=' || *c == ':') {
+        rest = &rest[1..];
+        while rest.first().is_some_and(|(_, c)| is_space(*c)) {
+            rest = &rest[1..];
+        }
+    }
+    Ok((unescape(&line[..end])?, unescape(rest)?))
+}
+
+/// Unescapes characters, joining surrogate pairs of `\uXXXX`.
+fn unescape(chars: &[(usize, char)]) -> Result<String, usize> {
+    let mut units = Vec::new();
+    let mut i = 0;
+    while let Some((offset, c)) = chars.get(i) {
+        i += 1;
+        if *c != '\\' {
+            units.extend(c.encode_utf16(&mut [0; 2]).iter());
+            continue;
+        }
+        let e = match chars.get(i) {
+            Some((_, e)) => *e,
+            // A backslash at the end of the input.
+            None => break,
+        };
+        i += 1;
+        match e {
+            't' => units.push('\t' as u16),
+            'n' => units.push('\n' as u16),
+            'r' => units.push('\r' as u16),
+            'f' => units.push('\u{c}' as u16),
+            'u' => {
+                let hex = chars
+                    .get(i..i + 4)
+                    .map(|h| h.iter().map(|(_, c)| *c).collect::<String>())
+                    .filter(|h| h.chars().all(|c| c.is_ascii_hexdigit()))
+                    .ok_or(*offset)?;
+                units.push(u16::from_str_radix(&hex, 16).or(Err(*offset))?);
+                i += 4;
+            }
+            e => units.extend(e.encode_utf16(&mut [0; 2]).iter()),
+        }
+    }
+    // Lone surrogates can't be in strings.
+    String::from_utf16(&units).map_err(|_| chars[0].0)
+}
@@ -0,0 +1,691 @@
+//! Event-based JSON(JSON5) Parser
+//!
+//! Reads the input incrementally, and reports values as events, keeping only
+//! the nesting of containers in the memory.
+use super::CombinedError;
+use crate::{Error, Map, Value};
+use std::convert::TryFrom;
+use std::io::{self, BufRead};
+use unicode_categories::UnicodeCategories;
+
+/// An event of the document.
+///
+/// An array is reported as `StartArray`, events of the elements, and
+/// `EndArray`. A map is reported as `StartMap`, pairs of `Key` and events of
+/// the value, and `EndMap`.
+#[derive(Clone, Debug)]
+pub enum Event {
+    StartArray,
+    EndArray,
+    StartMap,
+    /// A key of the map, followed by events of its value.
+    Key(String),
+    EndMap,
+    /// A scalar value, never `Value::Array` nor `Value::Map`.
+    Value(Value),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Container {
+    Array,
+    Map,
+}
+
+/// What is expected next.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum State {
+    /// A value, at the root or after a key.
+    Value,
+    /// The first element or the end of the array.
+    FirstElement,
+    /// A comma or the end of the array.
+    NextElement,
+    /// The first key or the end of the map.
+    FirstKey,
+    /// A comma or the end of the map.
+    NextKey,
+    /// The end of the input, or another document if reading multiple ones.
+    End,
+    /// Finished, or failed.
+    Done,
+}
+
+/// The length of lines kept to show errors in them.
+const LINE_LIMIT: usize = 1024;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Location {
+    line: usize,
+    column: usize,
+    offset: usize,
+}
+
+/// The iterator over events of a JSON(JSON5) document read from `R`, made by
+/// `JsonParser::events` or `Json5Parser::events`.
+///
+/// Errors are reported in place of events, and then the iteration ends. The
+/// input after the document is checked only if the iteration goes to the end.
+/// Duplicate keys in a map are reported as they are, unlike `parse` which
+/// keeps the last value, not to keep every key in the memory.
+#[derive(Debug)]
+pub struct Events<R> {
+    reader: io::BufReader<R>,
+    json5: bool,
+    /// The next character, `Some(None)` at the end of the input.
+    peeked: Option<Option<char>>,
+    /// The location of the next character.
+    location: Location,
+    /// The current line so far, or `None` if it is too long to be kept.
+    line: Option<String>,
+    stack: Vec<Container>,
+    state: State,
+    /// Whether to read multiple documents.
+    multiple: bool,
+    /// Whether a record separator has been read, making documents records of
+    /// RFC 7464.
+    records: bool,
+}
+
+impl<R: io::Read> Events<R> {
+    pub(crate) fn new(reader: R, json5: bool) -> Self {
+        Self {
+            reader: io::BufReader::new(reader),
+            json5,
+            peeked: None,
+            location: Location {
+                line: 1,
+                column: 1,
+                offset: 0,
+            },
+            line: Some(String::new()),
+            stack: Vec::new(),
+            state: State::Value,
+            multiple: false,
+            records: false,
+        }
+    }
+
+    /// Reads documents one after another, separated by whitespace or record
+    /// separators (RFC 7464), or even concatenated.
+    pub(crate) fn multiple(mut self) -> Self {
+        self.multiple = true;
+        self
+    }
+
+    fn error_at(&self, location: Location, expected: &[&'static str]) -> CombinedError {
+        CombinedError::Parse(self.parse_error(location, expected))
+    }
+
+    fn parse_error(&self, location: Location, expected: &[&'static str]) -> Error {
+        Error::with_location(
+            if self.json5 { "JSON5" } else { "JSON" },
+            location.line,
+            location.column,
+            location.offset,
+            expected.iter().copied(),
+        )
+    }
+
+    /// Keeps the line of the error in it, reading the rest of the line.
+    fn snippet(&mut self, e: Error) -> Error {
+        if e.line() != self.location.line {
+            return e;
+        }
+        // The error is reported even if the rest can't be read.
+        while let Ok(Some(c)) = self.peek() {
+            if c == '\n' || self.line.is_none() {
+                break;
+            }
+            self.bump();
+        }
+        match self.line.take() {
+            Some(line) => e.with_snippet(line),
+            None => e,
+        }
+    }
+
+    /// Ends the iteration on the error, keeping the line in it.
+    fn fail(&mut self, e: CombinedError) -> CombinedError {
+        self.state = State::Done;
+        match e {
+            CombinedError::Parse(e) => CombinedError::Parse(self.snippet(e)),
+            e => e,
+        }
+    }
+
+    /// Skips the rest of the record which failed to parse, up to the next
+    /// record separator.
+    fn skip_record(&mut self) -> Result<(), CombinedError> {
+        loop {
+            match self.peek() {
+                Ok(Some('\u{1e}')) | Ok(None) => break,
+                Ok(Some(_)) => self.bump(),
+                // Invalid bytes have been consumed.
+                Err(CombinedError::Parse(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        self.stack.clear();
+        self.state = State::Value;
+        Ok(())
+    }
+
+    fn error(&self, expected: &[&'static str]) -> CombinedError {
+        self.error_at(self.location, expected)
+    }
+
+    fn byte(&mut self) -> io::Result<Option<u8>> {
+        let b = match self.reader.fill_buf()? {
+            [] => return Ok(None),
+            buf => buf[0],
+        };
+        self.reader.consume(1);
+        Ok(Some(b))
+    }
+
+    /// Decodes a character from UTF-8.
+    fn decode(&mut self) -> Result<Option<char>, CombinedError> {
+        let first = match self.byte()? {
+            Some(b) => b,
+            None => return Ok(None),
+        };
+        let len = match first {
+            0x00..=0x7f => return Ok(Some(first as char)),
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => return Err(self.error(&["UTF-8"])),
+        };
+        let mut bytes = [first, 0, 0, 0];
+        for b in &mut bytes[1..len] {
+            *b = match self.byte()? {
+                Some(b) => b,
+                None => return Err(self.error(&["UTF-8"])),
+            };
+        }
+        match std::str::from_utf8(&bytes[..len]) {
+            Ok(s) => Ok(s.chars().next()),
+            Err(_) => Err(self.error(&["UTF-8"])),
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<char>, CombinedError> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.decode()?);
+        }
+        Ok(self.peeked.unwrap())
+    }
+
+    /// Consumes the peeked character.
+    fn bump(&mut self) {
+        if let Some(Some(c)) = self.peeked.take() {
+            self.location.offset += c.len_utf8();
+            if c == '\n' {
+                self.location.line += 1;
+                self.location.column = 1;
+                self.line = Some(String::new());
+            } else {
+                self.location.column += 1;
+                self.line = self.line.take().filter(|l| l.len() < LINE_LIMIT);
+                if let Some(line) = &mut self.line {
+                    line.push(c);
+                }
+            }
+        }
+    }
+
+    fn take(&mut self) -> Result<Option<char>, CombinedError> {
+        let c = self.peek()?;
+        self.bump();
+        Ok(c)
+    }
+
+    fn skip_whitespace(&mut self) -> Result<(), CombinedError> {
+        loop {
+            match self.peek()? {
+                Some(' ' | '\n' | '\r' | '\t') => self.bump(),
+                Some(c) if self.json5 && (c.is_whitespace() || c == '\u{feff}') => self.bump(),
+                Some('\u{1e}') if self.multiple && self.stack.is_empty() => {
+                    self.records = true;
+                    self.bump();
+                }
+                Some('/') if self.json5 => self.comment()?,
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn comment(&mut self) -> Result<(), CombinedError> {
+        self.bump();
+        match self.take()? {
+            Some('/') => {
+                while let Some(c) = self.peek()? {
+                    if matches!(c, '\n' | '\r' | '\u{2028}' | '\u{2029}') {
+                        break;
+                    }
+                    self.bump();
+                }
+            }
+            Some('*') => loop {
+                match self.take()? {
+                    Some('*') if self.peek()? == Some('/') => {
+                        self.bump();
+                        break;
+                    }
+                    Some(_) => {}
+                    None => return Err(self.error(&["\"*/\""])),
+                }
+            },
+            _ => return Err(self.error(&["\"/\"", "\"*\""])),
+        }
+        Ok(())
+    }
+
+    fn event(&mut self) -> Result<Option<Event>, CombinedError> {
+        if self.state == State::Done {
+            return Ok(None);
+        }
+        self.skip_whitespace()?;
+        let event = match (self.state, self.peek()?) {
+            (State::End, None) | (State::Value, None) if self.multiple && self.stack.is_empty() => {
+                self.state = State::Done;
+                return Ok(None);
+            }
+            (State::End, None) => {
+                self.state = State::Done;
+                return Ok(None);
+            }
+            (State::End, _) if self.multiple => self.value()?,
+            (State::End, _) => return Err(self.error(&["EOF"])),
+            (State::FirstElement, Some(']')) | (State::NextElement, Some(']')) => self.close(),
+            (State::FirstKey, Some('}')) | (State::NextKey, Some('}')) => self.close(),
+            (State::Value, _) | (State::FirstElement, _) => self.value()?,
+            (State::FirstKey, _) => self.key()?,
+            (State::NextElement, Some(',')) => {
+                self.bump();
+                self.skip_whitespace()?;
+                // Trailing commas are allowed in JSON5.
+                if self.json5 && self.peek()? == Some(']') {
+                    self.close()
+                } else {
+                    self.value()?
+                }
+            }
+            (State::NextKey, Some(',')) => {
+                self.bump();
+                self.skip_whitespace()?;
+                if self.json5 && self.peek()? == Some('}') {
+                    self.close()
+                } else {
+                    self.key()?
+                }
+            }
+            (State::NextElement, _) => return Err(self.error(&["\",\"", "\"]\""])),
+            (State::NextKey, _) => return Err(self.error(&["\",\"", "\"}\""])),
+            (State::Done, _) => unreachable!(),
+        };
+        Ok(Some(event))
+    }
+
+    /// Sets the state after a value.
+    fn end_value(&mut self) {
+        self.state = match self.stack.last() {
+            None => State::End,
+            Some(Container::Array) => State::NextElement,
+            Some(Container::Map) => State::NextKey,
+        };
+    }
+
+    /// Consumes the peeked bracket which closes the container.
+    fn close(&mut self) -> Event {
+        self.bump();
+        let container = self.stack.pop();
+        self.end_value();
+        match container {
+            Some(Container::Array) => Event::EndArray,
+            Some(Container::Map) => Event::EndMap,
+            None => unreachable!(),
+        }
+    }
+
+    fn value(&mut self) -> Result<Event, CombinedError> {
+        let event = match self.peek()? {
+            Some('[') => {
+                self.bump();
+                self.stack.push(Container::Array);
+                self.state = State::FirstElement;
+                return Ok(Event::StartArray);
+            }
+            Some('{') => {
+                self.bump();
+                self.stack.push(Container::Map);
+                self.state = State::FirstKey;
+                return Ok(Event::StartMap);
+            }
+            Some('"') => Event::Value(Value::String(self.string('"')?)),
+            Some('\'') if self.json5 => Event::Value(Value::String(self.string('\'')?)),
+            Some(c) if c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.') => {
+                Event::Value(self.word()?)
+            }
+            _ => return Err(self.error(&["value"])),
+        };
+        self.end_value();
+        Ok(event)
+    }
+
+    /// Reads literals and numbers.
+    fn word(&mut self) -> Result<Value, CombinedError> {
+        let start = self.location;
+        let mut word = String::new();
+        while let Some(c) = self.peek()? {
+            if !(c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')) {
+                break;
+            }
+            word.push(c);
+            self.bump();
+        }
+        match word.as_str() {
+            "null" => Ok(Value::Null),
+            "true" => Ok(Value::Boolean(true)),
+            "false" => Ok(Value::Boolean(false)),
+            _ => {
+                let number =
+                    number(&word, self.json5).ok_or_else(|| self.error_at(start, &["value"]))?;
+                // A number at the root of a record may have been truncated,
+                // unless whitespace follows it (RFC 7464, Section 2.4).
+                if self.records
+                    && self.stack.is_empty()
+                    && !matches!(self.peek()?, Some(' ' | '\n' | '\r' | '\t'))
+                {
+                    return Err(self.error(&["whitespace"]));
+                }
+                Ok(number)
+            }
+        }
+    }
+
+    fn key(&mut self) -> Result<Event, CombinedError> {
+        let key = match self.peek()? {
+            Some('"') => self.string('"')?,
+            Some('\'') if self.json5 => self.string('\'')?,
+            Some(c) if self.json5 && (is_ident_start(c) || c == '\\') => self.ident()?,
+            _ => return Err(self.error(&["key", "\"}\""])),
+        };
+        self.skip_whitespace()?;
+        if self.peek()? != Some(':') {
+            return Err(self.error(&["\":\""]));
+        }
+        self.bump();
+        self.state = State::Value;
+        Ok(Event::Key(key))
+    }
+
+    fn ident(&mut self) -> Result<String, CombinedError> {
+        let mut ident = String::new();
+        while let Some(c) = self.peek()? {
+            if c == '\\' {
+                self.bump();
+                if self.take()? != Some('u') {
+                    return Err(self.error(&["\"u\""]));
+                }
+                ident.push(self.unicode()?);
+            } else if is_ident_start(c) || !ident.is_empty() && is_ident_part(c) {
+                ident.push(c);
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        Ok(ident)
+    }
+
+    fn string(&mut self, quote: char) -> Result<String, CombinedError> {
+        self.bump();
+        let mut string = String::new();
+        loop {
+            match self.peek()? {
+                Some(c) if c == quote => {
+                    self.bump();
+                    return Ok(string);
+                }
+                Some('\\') => {
+                    self.bump();
+                    string.extend(self.escape()?);
+                }
+                // Line breaks must be escaped in JSON5.
+                Some('\n' | '\r') if self.json5 => break,
+                // The record has been truncated.
+                Some('\u{1e}') if self.records => break,
+                Some(c) => {
+                    self.bump();
+                    string.push(c);
+                }
+                None => break,
+            }
+        }
+        Err(self.error(&[if quote == '"' { "\"\\\"\"" } else { "\"'\"" }]))
+    }
+
+    /// Reads an escape sequence after a backslash, `None` for line continuations.
+    fn escape(&mut self) -> Result<Option<char>, CombinedError> {
+        let location = self.location;
+        let c = match self.take()? {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\x08',
+            Some('f') => '\x0c',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => self.unicode()?,
+            Some('\'') if self.json5 => '\'',
+            Some('v') if self.json5 => '\x0b',
+            Some('x') if self.json5 => self.hex(2)? as u8 as char,
+            Some('\r') if self.json5 => {
+                if self.peek()? == Some('\n') {
+                    self.bump();
+                }
+                return Ok(None);
+            }
+            Some('\n' | '\u{2028}' | '\u{2029}') if self.json5 => return Ok(None),
+            Some(c) if self.json5 => c,
+            _ => return Err(self.error_at(location, &["escape"])),
+        };
+        Ok(Some(c))
+    }
+
+    /// Reads 4 hex digits after `\u`, combining surrogate pairs.
+    fn unicode(&mut self) -> Result<char, CombinedError> {
+        let location = self.location;
+        let mut code = self.hex(4)?;
+        if (0xd800..0xdc00).contains(&code) && self.peek()? == Some('\\') {
+            self.bump();
+            if self.take()? != Some('u') {
+                return Err(self.error(&["\"u\""]));
+            }
+            let low = self.hex(4)?;
+            if (0xdc00..0xe000).contains(&low) {
+                code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+            }
+        }
+        char::try_from(code).map_err(|_| self.error_at(location, &["escape"]))
+    }
+
+    fn hex(&mut self, digits: usize) -> Result<u32, CombinedError> {
+        let mut code = 0;
+        for _ in 0..digits {
+            match self.peek()?.and_then(|c| c.to_digit(16)) {
+                Some(d) => code = code * 16 + d,
+                None => return Err(self.error(&["hexadecimal digit"])),
+            }
+            self.bump();
+        }
+        Ok(code)
+    }
+}
+
+impl<R: io::Read> Iterator for Events<R> {
+    type Item = Result<Event, CombinedError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.event() {
+            Ok(event) => event.map(Ok),
+            Err(e) => Some(Err(self.fail(e))),
+        }
+    }
+}
+
+/// The iterator over documents read from `R`, made by `JsonParser::documents`
+/// or `Json5Parser::documents`.
+///
+/// Errors are reported in place of documents, and then the iteration ends,
+/// except that records of RFC 7464 which fail to parse, like truncated ones,
+/// are skipped as the RFC says. Duplicate keys are merged as `parse` does,
+/// where the last value wins.
+#[derive(Debug)]
+pub struct Documents<R> {
+    events: Events<R>,
+}
+
+impl<R: io::Read> Documents<R> {
+    pub(crate) fn new(reader: R, json5: bool) -> Self {
+        Self {
+            events: Events::new(reader, json5).multiple(),
+        }
+    }
+
+    fn document(&mut self) -> Result<Option<Value>, CombinedError> {
+        // Containers being built, with the last key of maps.
+        let mut stack: Vec<(Value, Option<String>)> = Vec::new();
+        loop {
+            let event = match self.events.event() {
+                Ok(event) => event,
+                Err(CombinedError::Parse(_)) if self.events.records => {
+                    if let Err(e) = self.events.skip_record() {
+                        return Err(self.events.fail(e));
+                    }
+                    stack.clear();
+                    continue;
+                }
+                Err(e) => return Err(self.events.fail(e)),
+            };
+            let value = match event {
+                None => return Ok(None),
+                Some(Event::StartArray) => {
+                    stack.push((Value::Array(Vec::new()), None));
+                    continue;
+                }
+                Some(Event::StartMap) => {
+                    stack.push((Value::Map(Map::new()), None));
+                    continue;
+                }
+                Some(Event::Key(k)) => {
+                    stack.last_mut().unwrap().1 = Some(k);
+                    continue;
+                }
+                Some(Event::EndArray) | Some(Event::EndMap) => stack.pop().unwrap().0,
+                Some(Event::Value(v)) => v,
+            };
+            match stack.last_mut() {
+                None => return Ok(Some(value)),
+                Some((Value::Array(vs), _)) => vs.push(value),
+                Some((Value::Map(m), key)) => {
+                    m.insert(key.take().unwrap(), value);
+                }
+                Some(_) => unreachable!(),
+            }
+        }
+    }
+}
+
+impl<R: io::Read> Iterator for Documents<R> {
+    type Item = Result<Value, CombinedError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.document().transpose()
+    }
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_letter_uppercase()
+        || c.is_letter_lowercase()
+        || c.is_letter_titlecase()
+        || c.is_letter_modifier()
+        || c.is_letter_other()
+        || c == '$'
+        || c == '_'
+}
+
+fn is_ident_part(c: char) -> bool {
+    is_ident_start(c)
+        || c.is_mark_nonspacing()
+        || c.is_mark_spacing_combining()
+        || c.is_number_decimal_digit()
+        || c.is_punctuation_connector()
+        || c == '\u{200c}'
+        || c == '\u{200d}'
+}
+
+/// Reads a number, as the grammars of `JsonParser` and `Json5Parser` do.
+fn number(s: &str, json5: bool) -> Option<Value> {
+    let (negative, unsigned) = match s.as_bytes().first()? {
+        b'-' => (true, &s[1..]),
+        b'+' if json5 => (false, &s[1..]),
+        _ => (false, s),
+    };
+    let value = match unsigned {
+        "Infinity" if json5 => Value::Float(f64::INFINITY),
+        "NaN" if json5 => Value::Float(f64::NAN),
+        _ if json5 && unsigned.starts_with("0x") => {
+            Value::Int(i128::from_str_radix(&unsigned[2..], 16).ok()?)
+        }
+        _ => decimal(unsigned, json5)?,
+    };
+    Some(match value {
+        Value::Int(i) if negative => Value::Int(-i),
+        Value::Float(f) if negative => Value::Float(-f),
+        value => value,
+    })
+}
+
+fn decimal(s: &str, json5: bool) -> Option<Value> {
+    let bytes = s.as_bytes();
+    let digits = |i: usize| bytes[i..].iter().take_while(|b| b.is_ascii_digit()).count();
+
+    let int = digits(0);
+    if int > 1 && bytes[0] == b'0' {
+        return None;
+    }
+    let mut i = int;
+    if bytes.get(i) == Some(&b'.') {
+        let fraction = digits(i + 1);
+        if fraction == 0 {
+            return None;
+        }
+        i += 1 + fraction;
+    }
+    // Only JSON5 allows numbers starting with the decimal point.
+    if int == 0 && (!json5 || i == 0) {
+        return None;
+    }
+    if matches!(bytes.get(i), Some(b'e' | b'E')) {
+        i += 1;
+        if matches!(bytes.get(i), Some(b'+' | b'-')) {
+            i += 1;
+        }
+        let exponent = digits(i);
+        if exponent == 0 || exponent > 1 && bytes[i] == b'0' {
+            return None;
+        }
+        i += exponent;
+    }
+
+    if i != bytes.len() {
+        None
+    } else if i == int {
+        s.parse().ok().map(Value::Int)
+    } else {
+        s.parse().ok().map(Value::Float)
+    }
+}
@@ -1,13 +1,371 @@
 //! TOML Parser Implementation
 
-use crate::{Value, Parser};
+use crate::datetime::*;
+use crate::{Error, Parser, Value};
+use indexmap::map::{Entry, IndexMap};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct TomlParser;
 
+/// A top-level expression of a TOML document.
+#[derive(Clone, Debug)]
+enum Expr {
+    Table(Vec<String>),
+    ArrayTable(Vec<String>),
+    KeyVal(Vec<String>, Value),
+}
+
+/// How a table came into existence, which decides whether it can be extended.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Kind {
+    /// Created as a parent of a `[header]`, may be defined later.
+    Implicit,
+    /// Defined by a `[header]` or `[[header]]`.
+    Header,
+    /// Created by a dotted key.
+    Dotted,
+}
+
+#[derive(Clone, Debug)]
+enum Item {
+    /// Plain values, including inline tables and arrays which can't be extended.
+    Value(Value),
+    Table(Table),
+    Array(Vec<Table>),
+}
+
+#[derive(Clone, Debug)]
+struct Table {
+    kind: Kind,
+    entries: IndexMap<String, Item>,
+}
+
+impl Table {
+    fn new(kind: Kind) -> Self {
+        Self {
+            kind,
+            entries: IndexMap::new(),
+        }
+    }
+
+    /// Inserts a value with a (possibly dotted) key.
+    fn insert(&mut self, keys: &[String], value: Value) -> Result<(), &'static str> {
+        let (last, init) = keys.split_last().unwrap();
+        let mut table = self;
+        for key in init {
+            let item = table
+                .entries
+                .entry(key.clone())
+                .or_insert_with(|| Item::Table(Table::new(Kind::Dotted)));
+            table = match item {
+                Item::Table(t) if t.kind == Kind::Dotted => t,
+                _ => return Err("unique key"),
+            };
+        }
+
+        match table.entries.entry(last.clone()) {
+            Entry::Vacant(e) => {
+                e.insert(Item::Value(value));
+                Ok(())
+            }
+            Entry::Occupied(_) => Err("unique key"),
+        }
+    }
+
+    /// Defines a table by `[keys]`, or a new element of `[[keys]]`.
+    fn define(&mut self, keys: &[String], array: bool) -> Result<(), &'static str> {
+        let (last, init) = keys.split_last().unwrap();
+        let mut table = self;
+        for key in init {
+            let item = table
+                .entries
+                .entry(key.clone())
+                .or_insert_with(|| Item::Table(Table::new(Kind::Implicit)));
+            table = match item {
+                Item::Table(t) => t,
+                Item::Array(a) => a.last_mut().unwrap(),
+                Item::Value(_) => return Err("table"),
+            };
+        }
+
+        match (table.entries.entry(last.clone()), array) {
+            (Entry::Vacant(e), false) => {
+                e.insert(Item::Table(Table::new(Kind::Header)));
+            }
+            (Entry::Vacant(e), true) => {
+                e.insert(Item::Array(vec![Table::new(Kind::Header)]));
+            }
+            (Entry::Occupied(mut e), false) => match e.get_mut() {
+                Item::Table(t) if t.kind == Kind::Implicit => t.kind = Kind::Header,
+                _ => return Err("unique table"),
+            },
+            (Entry::Occupied(mut e), true) => match e.get_mut() {
+                Item::Array(a) => a.push(Table::new(Kind::Header)),
+                _ => return Err("array of tables"),
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Gets the table defined by the last header of `keys`.
+    fn get_mut(&mut self, keys: &[String]) -> &mut Table {
+        keys.iter()
+            .fold(self, |table, key| match table.entries.get_mut(key) {
+                Some(Item::Table(t)) => t,
+                Some(Item::Array(a)) => a.last_mut().unwrap(),
+                _ => unreachable!(),
+            })
+    }
+}
+
+impl From<Table> for Value {
+    fn from(table: Table) -> Self {
+        Value::Map(
+            table
+                .entries
+                .into_iter()
+                .map(|(k, item)| {
+                    let v = match item {
+                        Item::Value(v) => v,
+                        Item::Table(t) => t.into(),
+                        Item::Array(a) => Value::Array(a.into_iter().map(Value::from).collect()),
+                    };
+                    (k, v)
+                })
+                .collect(),
+        )
+    }
+}
+
+peg::parser! {grammar toml_parser() for str {
+    use super::{Expr, Kind, Table};
+    use std::convert::TryFrom;
+    use std::iter::FromIterator;
+
+    pub rule toml() -> Vec<(usize, Expr)>
+        = "\u{feff}"? e:(expression() ** newline()) { e.into_iter().flatten().collect() }
+
+    rule expression() -> Option<(usize, Expr)>
+        = ws() p:position!() e:(table() / keyval_expr()) ws() comment()? { Some((p, e)) }
+        / ws() comment()? { None }
+
+    rule ws() = quiet!{[' ' | '\t']*}
+
+    rule newline() = "\n" / "\r\n"
+
+    rule comment() = "#" [c if c == '\t' || !(c < ' ' || c == '\x7f')]*
+
+    // Whitespaces, comments and newlines inside arrays
+    rule wscn() = ws() (comment()? newline() ws())*
+
+    rule table() -> Expr
+        = "[[" ws() k:key() ws() "]]" { Expr::ArrayTable(k) }
+        / "[" ws() k:key() ws() "]" { Expr::Table(k) }
+
+    rule keyval_expr() -> Expr
+        = kv:keyval() { Expr::KeyVal(kv.0, kv.1) }
+
+    rule keyval() -> (Vec<String>, Value)
+        = k:key() ws() "=" ws() v:value() { (k, v) }
+
+    rule key() -> Vec<String>
+        = simple_key() ++ (ws() "." ws())
+
+    rule simple_key() -> String
+        = s:$(['A'..='Z' | 'a'..='z' | '0'..='9' | '_' | '-']+) { s.to_string() }
+        / basic_string()
+        / literal_string()
+
+    rule value() -> Value
+        = s:string()       { Value::String(s) }
+        / "true"           { Value::Boolean(true) }
+        / "false"          { Value::Boolean(false) }
+        / array()
+        / inline_table()
+        / d:datetime()     { Value::DateTime(d) }
+        / float()
+        / integer()
+
+    rule array() -> Value
+        = "[" wscn() "]" { Value::Array(Vec::new()) }
+        / "[" v:(array_value() ++ ",") ("," wscn())? "]" { Value::Array(v) }
+
+    rule array_value() -> Value
+        = wscn() v:value() wscn() { v }
+
+    rule inline_table() -> Value
+        = "{" ws() kv:(keyval() ** (ws() "," ws())) ws() "}" {?
+            let mut table = Table::new(Kind::Dotted);
+            for (k, v) in kv {
+                table.insert(&k, v)?;
+            }
+            Ok(table.into())
+        }
+
+    rule string() -> String
+        = ml_basic_string()
+        / basic_string()
+        / ml_literal_string()
+        / literal_string()
+
+    rule basic_string() -> String
+        = "\"" c:basic_char()* "\"" { String::from_iter(c) }
+
+    rule basic_char() -> char
+        = c:$([c if c != '"' && c != '\\' && (c == '\t' || !(c < ' ' || c == '\x7f'))]) {
+            c.chars().next().unwrap()
+        }
+        / "\\" e:escape() { e }
+
+    rule ml_basic_string() -> String
+        = "\"\"\"" newline()? c:ml_basic_chunk()* e:ml_basic_end() { [c.concat(), e.to_string()].concat() }
+
+    // Up to two quotation marks are allowed inside the string.
+    rule ml_basic_chunk() -> String
+        = q:$("\"\"" / "\"")? c:ml_basic_char() {
+            let mut s = q.unwrap_or("").to_string();
+            s.extend(c);
+            s
+        }
+
+    rule ml_basic_char() -> Option<char>
+        = c:$([c if c != '"' && c != '\\' && (c == '\t' || !(c < ' ' || c == '\x7f'))]) {
+            c.chars().next()
+        }
+        / newline() { Some('\n') }
+        / "\\" ws() newline() ([' ' | '\t'] / newline())* { None }
+        / "\\" e:escape() { Some(e) }
+
+    rule ml_basic_end() -> &'static str
+        = "\"\"\"\"\"" { "\"\"" }
+        / "\"\"\"\"" { "\"" }
+        / "\"\"\"" { "" }
+
+    rule escape() -> char
+        = "\"" { '"' }
+        / "\\" { '\\' }
+        / "b"  { '\x08' }
+        / "f"  { '\x0c' }
+        / "n"  { '\n' }
+        / "r"  { '\r' }
+        / "t"  { '\t' }
+        / "u" h:$(hex()*<4>) {? char::try_from(u32::from_str_radix(h, 16).or(Err("hexchar"))?).or(Err("escape")) }
+        / "U" h:$(hex()*<8>) {? char::try_from(u32::from_str_radix(h, 16).or(Err("hexchar"))?).or(Err("escape")) }
+
+    rule literal_string() -> String
+        = "'" s:$([c if c != '\'' && (c == '\t' || !(c < ' ' || c == '\x7f'))]*) "'" { s.to_string() }
+
+    rule ml_literal_string() -> String
+        = "'''" newline()? c:ml_literal_chunk()* e:ml_literal_end() { [c.concat(), e.to_string()].concat() }
+
+    // Up to two apostrophes are allowed inside the string.
+    rule ml_literal_chunk() -> String
+        = q:$("''" / "'")? c:ml_literal_char() { [q.unwrap_or(""), c].concat() }
+
+    rule ml_literal_char() -> &'input str
+        = $([c if c != '\'' && (c == '\t' || !(c < ' ' || c == '\x7f'))])
+        / newline() { "\n" }
+
+    rule ml_literal_end() -> &'static str
+        = "'''''" { "''" }
+        / "''''" { "'" }
+        / "'''" { "" }
+
+    rule integer() -> Value
+        = "0x" h:$(hex() ("_"? hex())*) {? int(h, 16) }
+        / "0o" o:$(['0'..='7'] ("_"? ['0'..='7'])*) {? int(o, 8) }
+        / "0b" b:$(['0' | '1'] ("_"? ['0' | '1'])*) {? int(b, 2) }
+        / d:$(dec_int()) {? int(d, 10) }
+
+    rule float() -> Value
+        = s:sign()? "inf" { Value::Float(f64::INFINITY * s.unwrap_or(1) as f64) }
+        / s:sign()? "nan" { Value::Float(f64::NAN.copysign(s.unwrap_or(1) as f64)) }
+        / f:$(dec_int() (fraction() exponent()? / exponent())) {?
+            f.replace('_', "").parse().map(Value::Float).or(Err("float"))
+        }
+
+    rule dec_int() = ("+" / "-")? (['1'..='9'] ("_"? digit())* / "0")
+
+    rule fraction() = "." digit() ("_"? digit())*
+
+    rule exponent() = ['e' | 'E'] ("+" / "-")? digit() ("_"? digit())*
+
+    rule digit() = ['0'..='9']
+
+    rule hex() -> &'input str
+        = $(['0'..='9' | 'a'..='f' | 'A'..='F'])
+
+    rule datetime() -> DateTime
+        = d:date() t:(['T' | 't' | ' '] t:time() { t })? o:offset()? {?
+            DateTime::new(Some(d), t, o).or(Err("datetime"))
+        }
+        / t:time() {? DateTime::new(None, Some(t), None).or(Err("datetime")) }
+
+    rule date() -> Date
+        = y:digits4() "-" m:digits2() "-" d:digits2() {? Date::new(y, m, d).or(Err("date")) }
+
+    rule time() -> Time
+        = h:digits2() ":" m:digits2() ":" s:digits2() f:time_fraction()? {?
+            Time::new(h, m, s, f.unwrap_or(0)).or(Err("time"))
+        }
+
+    rule offset() -> Offset
+        = ['Z' | 'z'] {? Offset::new(0, 0).or(Err("offset")) }
+        / s:sign() h:digits2() ":" m:digits2() {?
+            Offset::new(s * (h as i8), m).or(Err("offset"))
+        }
+
+    rule sign() -> i8
+        = "+" {  1 }
+        / "-" { -1 }
+
+    rule digits4() -> u16
+        = y:$(['0'..='9']*<4>) {? y.parse().or(Err("year")) }
+
+    rule digits2() -> u8
+        = m:$(['0'..='9']*<2>) {? m.parse().or(Err("month")) }
+
+    // Digits beyond nanoseconds are truncated.
+    rule time_fraction() -> u32
+        = "." f:$(['0'..='9']+) {?
+            format!("{:0<9.9}", f).parse().or(Err("time_fraction"))
+        }
+}}
+
+/// Parses an integer, which TOML limits to 64 bits.
+fn int(s: &str, radix: u32) -> Result<Value, &'static str> {
+    i64::from_str_radix(&s.replace('_', ""), radix)
+        .map(|i| Value::Int(i.into()))
+        .or(Err("64 bit integer"))
+}
+
 impl Parser for TomlParser {
-    type Err = peg::error::ParseError<peg::str::LineCol>;
-    fn parse(&self, _s: &str) -> Result<Value, Self::Err> {
-        Ok(Value::Null)
+    const FORMAT: &'static str = "TOML";
+
+    fn parse(&self, s: &str) -> Result<Value, Error> {
+        let error_at = |pos, e| Error::new("TOML", s, pos, &[e]);
+        let exprs = toml_parser::toml(s).map_err(|e| Error::from_peg("TOML", e))?;
+
+        let mut root = Table::new(Kind::Header);
+        let mut current = Vec::new();
+        for (pos, expr) in exprs {
+            match expr {
+                Expr::Table(keys) => {
+                    root.define(&keys, false).map_err(|e| error_at(pos, e))?;
+                    current = keys;
+                }
+                Expr::ArrayTable(keys) => {
+                    root.define(&keys, true).map_err(|e| error_at(pos, e))?;
+                    current = keys;
+                }
+                Expr::KeyVal(keys, value) => root
+                    .get_mut(&current)
+                    .insert(&keys, value)
+                    .map_err(|e| error_at(pos, e))?,
+            }
+        }
+
+        Ok(root.into())
     }
 }
@@ -0,0 +1,581 @@
+//! XML Parser
+use crate::xml::{is_char, is_name_char, is_name_start, is_qualified_name};
+use crate::{Error, Map, Parser, Value};
+use std::collections::HashMap;
+
+/// XML 1.0 Parser, which maps the document onto `Value` as follows.
+///
+/// - The root element is a map with a single key of its name.
+/// - Elements without attributes and children are strings of their text, or
+///   null if they are empty.
+/// - Other elements are maps, where attributes are `@name` keys, child elements
+///   are keys of their names, and the text is `#text`. Whitespace between child
+///   elements is ignored, and text of mixed content is joined.
+/// - Repeated child elements are arrays, and with `always_array` any child
+///   element is wrapped in an array.
+///
+/// Values are always strings. Names are kept with their namespace prefixes,
+/// and namespace declarations are attributes like `@xmlns:soap`. Entities
+/// declared in the internal subset of the document type are expanded, where
+/// their replacement text is read as text, unlike XML 1.0 which reads it as
+/// markup: `<!ENTITY e "<b/>">` makes `&e;` the text `<b/>`, not an element.
+/// Elements can be nested up to 128 levels.
+#[derive(Clone, Debug, Default)]
+pub struct XmlParser {
+    always_array: bool,
+}
+
+impl XmlParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wraps child elements in arrays even if they are not repeated.
+    pub fn always_array(&mut self, always_array: bool) -> &mut Self {
+        self.always_array = always_array;
+        self
+    }
+}
+
+impl Parser for XmlParser {
+    const FORMAT: &'static str = "XML";
+
+    fn parse(&self, s: &str) -> Result<Value, Error> {
+        if let Some(i) = s.find(|c| !is_char(c)) {
+            return Err(Error::new("XML", s, i, &["XML character"]));
+        }
+        let mut reader = Reader {
+            input: s,
+            pos: 0,
+            config: self,
+            entities: HashMap::new(),
+            expanded: 0,
+            namespaces: vec!["xml".to_string(), "xmlns".to_string()],
+        };
+        reader.document()
+    }
+}
+
+/// The limit of the total length of expanded entities, against entities
+/// expanding exponentially.
+const EXPANSION_LIMIT: usize = 1 << 24;
+
+/// The limit of nested elements, not to overflow the stack.
+const DEPTH_LIMIT: usize = 128;
+
+fn is_space(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\r')
+}
+
+/// An element being read.
+#[derive(Default)]
+struct Element {
+    attributes: Map,
+    children: Map,
+    text: String,
+}
+
+struct Reader<'a> {
+    input: &'a str,
+    pos: usize,
+    config: &'a XmlParser,
+    /// Replacement texts of internal entities, or `None` for external ones.
+    entities: HashMap<String, Option<String>>,
+    /// The total length of expanded entities.
+    expanded: usize,
+    /// Namespace prefixes declared in the scope.
+    namespaces: Vec<String>,
+}
+
+impl<'a> Reader<'a> {
+    fn error(&self, expected: &[&'static str]) -> Error {
+        self.error_at(self.pos, expected)
+    }
+
+    fn error_at(&self, offset: usize, expected: &[&'static str]) -> Error {
+        Error::new("XML", self.input, offset, expected)
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        let found = self.rest().starts_with(s);
+        if found {
+            self.pos += s.len();
+        }
+        found
+    }
+
+    fn expect(&mut self, s: &str, token: &'static str) -> Result<(), Error> {
+        if self.eat(s) {
+            Ok(())
+        } else {
+            Err(self.error(&[token]))
+        }
+    }
+
+    /// Skips whitespace, returning whether there is any.
+    fn skip_spaces(&mut self) -> bool {
+        let len = self.rest().len() - self.rest().trim_start_matches(is_space).len();
+        self.pos += len;
+        len > 0
+    }
+
+    /// Skips to the end of `end`.
+    fn skip_to(&mut self, end: &str, token: &'static str) -> Result<&'a str, Error> {
+        match self.rest().find(end) {
+            Some(i) => {
+                let skipped = &self.rest()[..i];
+                self.pos += i + end.len();
+                Ok(skipped)
+            }
+            None => Err(self.error_at(self.input.len(), &[token])),
+        }
+    }
+
+    fn name(&mut self) -> Result<&'a str, Error> {
+        let rest = self.rest();
+        if !rest.starts_with(is_name_start) {
+            return Err(self.error(&["name"]));
+        }
+        let len = rest.find(|c| !is_name_char(c)).unwrap_or(rest.len());
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    /// Reads a name, checking its namespace prefix is declared.
+    fn qualified_name(&mut self) -> Result<&'a str, Error> {
+        let start = self.pos;
+        let name = self.name()?;
+        if !is_qualified_name(name) {
+            return Err(self.error_at(start, &["qualified name"]));
+        }
+        match name.split_once(':') {
+            Some((prefix, _)) if !self.namespaces.iter().any(|p| p == prefix) => {
+                Err(self.error_at(start, &["declared namespace prefix"]))
+            }
+            _ => Ok(name),
+        }
+    }
+
+    /// Skips comments, processing instructions and whitespace.
+    fn misc(&mut self) -> Result<(), Error> {
+        loop {
+            if self.eat("<!--") {
+                self.skip_to("-->", "\"-->\"")?;
+            } else if self.rest().starts_with("<?") {
+                self.pi()?;
+            } else if !self.skip_spaces() {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Skips a processing instruction, or the XML declaration.
+    fn pi(&mut self) -> Result<(), Error> {
+        self.pos += 2;
+        self.name()?;
+        self.skip_to("?>", "\"?>\"")?;
+        Ok(())
+    }
+
+    fn document(&mut self) -> Result<Value, Error> {
+        self.eat("\u{feff}");
+        self.misc()?;
+        if self.eat("<!DOCTYPE") {
+            self.doctype()?;
+            self.misc()?;
+        }
+        if !self.rest().starts_with('<') {
+            return Err(self.error(&["\"<\""]));
+        }
+        let (name, value) = self.element(0)?;
+        self.misc()?;
+        if self.pos < self.input.len() {
+            return Err(self.error(&["EOF"]));
+        }
+        let mut root = Map::new();
+        root.insert(name, value);
+        Ok(Value::Map(root))
+    }
+
+    /// Reads the document type after `<!DOCTYPE`, keeping entities of the
+    /// internal subset.
+    fn doctype(&mut self) -> Result<(), Error> {
+        if !self.skip_spaces() {
+            return Err(self.error(&["whitespace"]));
+        }
+        self.name()?;
+        self.skip_spaces();
+        self.external_id()?;
+        self.skip_spaces();
+        if self.eat("[") {
+            loop {
+                self.misc()?;
+                if self.eat("]") {
+                    break;
+                } else if self.eat("<!ENTITY") {
+                    self.entity_decl()?;
+                } else if self.rest().starts_with("<!") {
+                    self.markup_decl()?;
+                } else if self.eat("%") {
+                    // Parameter entities are not expanded.
+                    self.name()?;
+                    self.expect(";", "\";\"")?;
+                } else {
+                    return Err(self.error(&["\"]\"", "markup declaration"]));
+                }
+            }
+            self.skip_spaces();
+        }
+        self.expect(">", "\">\"")
+    }
+
+    /// Reads an external ID if any, returning whether there is one.
+    fn external_id(&mut self) -> Result<bool, Error> {
+        if self.eat("SYSTEM") {
+            self.skip_spaces();
+            self.literal()?;
+        } else if self.eat("PUBLIC") {
+            self.skip_spaces();
+            self.literal()?;
+            self.skip_spaces();
+            self.literal()?;
+        } else {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Reads a quoted literal.
+    fn literal(&mut self) -> Result<&'a str, Error> {
+        let quote = match self.peek() {
+            Some(q @ '"') | Some(q @ '\'') => q,
+            _ => return Err(self.error(&["\"\\\"\"", "\"'\""])),
+        };
+        self.pos += 1;
+        match self.rest().find(quote) {
+            Some(i) => {
+                let literal = &self.rest()[..i];
+                self.pos += i + 1;
+                Ok(literal)
+            }
+            None => Err(self.error_at(
+                self.input.len(),
+                &[if quote == '"' { "\"\\\"\"" } else { "\"'\"" }],
+            )),
+        }
+    }
+
+    /// Skips a markup declaration other than entities, which may have quoted
+    /// literals.
+    fn markup_decl(&mut self) -> Result<(), Error> {
+        self.pos += 2;
+        loop {
+            match self.peek() {
+                Some('>') => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                Some('"') | Some('\'') => {
+                    self.literal()?;
+                }
+                Some(c) => self.pos += c.len_utf8(),
+                None => return Err(self.error(&["\">\""])),
+            }
+        }
+    }
+
+    /// Reads an entity declaration after `<!ENTITY`, where the first one wins.
+    fn entity_decl(&mut self) -> Result<(), Error> {
+        self.skip_spaces();
+        let parameter = self.eat("%");
+        self.skip_spaces();
+        let name = self.name()?;
+        self.skip_spaces();
+        let value = if self.external_id()? {
+            self.skip_spaces();
+            if self.eat("NDATA") {
+                self.skip_spaces();
+                self.name()?;
+            }
+            None
+        } else {
+            let start = self.pos + 1;
+            let literal = self.literal()?;
+            Some(self.char_refs(literal, start)?)
+        };
+        self.skip_spaces();
+        self.expect(">", "\">\"")?;
+        if !parameter && !self.entities.contains_key(name) {
+            self.entities.insert(name.to_string(), value);
+        }
+        Ok(())
+    }
+
+    /// Expands character references in the entity value at `offset`, keeping
+    /// entity references to be expanded on use.
+    fn char_refs(&self, literal: &str, offset: usize) -> Result<String, Error> {
+        let mut value = String::new();
+        let mut rest = literal;
+        while let Some(i) = rest.find("&#") {
+            value.push_str(&rest[..i]);
+            let at = offset + literal.len() - rest.len() + i;
+            let (c, len) = char_ref(&rest[i..]).ok_or_else(|| self.error_at(at, &["reference"]))?;
+            value.push(c);
+            rest = &rest[i + len..];
+        }
+        value.push_str(rest);
+        Ok(value)
+    }
+
+    /// Reads an element nested in `depth` elements, returning its name and
+    /// value.
+    fn element(&mut self, depth: usize) -> Result<(String, Value), Error> {
+        if depth >= DEPTH_LIMIT {
+            return Err(self.error(&["nesting within limits"]));
+        }
+        self.expect("<", "\"<\"")?;
+        let start = self.pos;
+        let name = self.name()?;
+        let mut element = Element::default();
+        let mut declared = 0;
+
+        // Namespaces are declared by attributes, which may follow prefixed ones.
+        let mut attributes = Vec::new();
+        loop {
+            let spaced = self.skip_spaces();
+            if self.rest().starts_with('>') || self.rest().starts_with("/>") {
+                break;
+            } else if !spaced {
+                return Err(self.error(&["whitespace", "\">\"", "\"/>\""]));
+            }
+            let offset = self.pos;
+            let attribute = self.name()?;
+            self.skip_spaces();
+            self.expect("=", "\"=\"")?;
+            self.skip_spaces();
+            let value = self.attribute_value()?;
+            if let Some(prefix) = attribute.strip_prefix("xmlns:") {
+                self.namespaces.push(prefix.to_string());
+                declared += 1;
+            }
+            let key = ["@", attribute].concat();
+            if element.attributes.contains_key(&key) {
+                return Err(self.error_at(offset, &["unique attribute"]));
+            }
+            element.attributes.insert(key, Value::String(value));
+            attributes.push(offset);
+        }
+        let end = self.pos;
+        for offset in attributes {
+            self.pos = offset;
+            self.qualified_name()?;
+        }
+        self.pos = start;
+        self.qualified_name()?;
+        self.pos = end;
+
+        if !self.eat("/>") {
+            self.pos += 1;
+            self.content(&mut element, depth)?;
+            let offset = self.pos;
+            if self.name()? != name {
+                return Err(self.error_at(offset, &["matching end tag"]));
+            }
+            self.skip_spaces();
+            self.expect(">", "\">\"")?;
+        }
+        self.namespaces.truncate(self.namespaces.len() - declared);
+        Ok((name.to_string(), self.value(element)))
+    }
+
+    /// Reads the content of an element nested in `depth` elements, until `</`.
+    fn content(&mut self, element: &mut Element, depth: usize) -> Result<(), Error> {
+        loop {
+            let rest = self.rest();
+            if rest.is_empty() {
+                return Err(self.error(&["\"</\""]));
+            } else if self.eat("</") {
+                return Ok(());
+            } else if self.eat("<!--") {
+                self.skip_to("-->", "\"-->\"")?;
+            } else if self.eat("<![CDATA[") {
+                let text = self.skip_to("]]>", "\"]]>\"")?;
+                element.text.push_str(&newlines(text));
+            } else if rest.starts_with("<?") {
+                self.pi()?;
+            } else if rest.starts_with('<') {
+                let (name, value) = self.element(depth + 1)?;
+                self.child(element, name, value);
+            } else if rest.starts_with('&') {
+                let text = self.reference()?;
+                element.text.push_str(&text);
+            } else {
+                let len = rest.find(&['<', '&'][..]).unwrap_or(rest.len());
+                element.text.push_str(&newlines(&rest[..len]));
+                self.pos += len;
+            }
+        }
+    }
+
+    fn child(&self, element: &mut Element, name: String, value: Value) {
+        match element.children.get_mut(&name) {
+            Some(Value::Array(vs)) => vs.push(value),
+            Some(v) => {
+                let first = std::mem::replace(v, Value::Null);
+                *v = Value::Array(vec![first, value]);
+            }
+            None if self.config.always_array => {
+                element.children.insert(name, Value::Array(vec![value]));
+            }
+            None => {
+                element.children.insert(name, value);
+            }
+        }
+    }
+
+    fn value(&self, element: Element) -> Value {
+        let Element {
+            attributes,
+            children,
+            text,
+        } = element;
+        if attributes.is_empty() && children.is_empty() {
+            return if text.is_empty() {
+                Value::Null
+            } else {
+                Value::String(text)
+            };
+        }
+        let mut map = attributes;
+        let blank = text.trim_matches(is_space).is_empty();
+        let ignored = text.is_empty() || blank && !children.is_empty();
+        map.extend(children);
+        if !ignored {
+            map.insert("#text".to_string(), Value::String(text));
+        }
+        Value::Map(map)
+    }
+
+    /// Reads a quoted attribute value, normalizing whitespace to spaces.
+    fn attribute_value(&mut self) -> Result<String, Error> {
+        let quote = match self.peek() {
+            Some(q @ '"') | Some(q @ '\'') => q,
+            _ => return Err(self.error(&["\"\\\"\"", "\"'\""])),
+        };
+        self.pos += 1;
+        let mut value = String::new();
+        loop {
+            let rest = self.rest();
+            match rest.chars().next() {
+                Some(c) if c == quote => {
+                    self.pos += 1;
+                    return Ok(value);
+                }
+                Some('<') => return Err(self.error(&["reference", "character"])),
+                Some('&') => value.push_str(&self.reference()?),
+                Some(_) => {
+                    let len = rest.find(&[quote, '<', '&'][..]).unwrap_or(rest.len());
+                    let text = newlines(&rest[..len]).replace(&['\t', '\n'][..], " ");
+                    value.push_str(&text);
+                    self.pos += len;
+                }
+                None => return Err(self.error(&[if quote == '"' { "\"\\\"\"" } else { "\"'\"" }])),
+            }
+        }
+    }
+
+    /// Reads a character or entity reference, returning its text.
+    fn reference(&mut self) -> Result<String, Error> {
+        let offset = self.pos;
+        if let Some((c, len)) = char_ref(self.rest()) {
+            self.pos += len;
+            return Ok(c.to_string());
+        } else if self.rest().starts_with("&#") {
+            return Err(self.error(&["reference"]));
+        }
+        self.pos += 1;
+        let name = self.name()?;
+        self.expect(";", "\";\"")?;
+        self.entity(name, offset, &mut Vec::new())
+    }
+
+    /// Expands the entity referred at `offset`, where `stack` has entities
+    /// being expanded.
+    fn entity(
+        &mut self,
+        name: &str,
+        offset: usize,
+        stack: &mut Vec<String>,
+    ) -> Result<String, Error> {
+        let text = match name {
+            "lt" => return Ok("<".to_string()),
+            "gt" => return Ok(">".to_string()),
+            "amp" => return Ok("&".to_string()),
+            "apos" => return Ok("'".to_string()),
+            "quot" => return Ok("\"".to_string()),
+            _ => match self.entities.get(name) {
+                Some(Some(text)) => text.clone(),
+                Some(None) => return Err(self.error_at(offset, &["internal entity"])),
+                None => return Err(self.error_at(offset, &["defined entity"])),
+            },
+        };
+        if stack.iter().any(|n| n == name) {
+            return Err(self.error_at(offset, &["non-recursive entity"]));
+        }
+        stack.push(name.to_string());
+        let mut expanded = String::new();
+        let mut rest = text.as_str();
+        while let Some(i) = rest.find('&') {
+            expanded.push_str(&rest[..i]);
+            rest = &rest[i..];
+            if let Some((c, len)) = char_ref(rest) {
+                expanded.push(c);
+                rest = &rest[len..];
+                continue;
+            }
+            let (name, after) = rest[1..]
+                .split_once(';')
+                .filter(|(n, _)| n.starts_with(is_name_start) && n.chars().all(is_name_char))
+                .ok_or_else(|| self.error_at(offset, &["reference"]))?;
+            expanded.push_str(&self.entity(name, offset, stack)?);
+            rest = after;
+        }
+        expanded.push_str(rest);
+        stack.pop();
+
+        self.expanded += expanded.len();
+        if self.expanded > EXPANSION_LIMIT {
+            return Err(self.error_at(offset, &["entity expansion within limits"]));
+        }
+        Ok(expanded)
+    }
+}
+
+/// Reads a character reference like `&#60;` or `&#x3C;`, returning the
+/// character and the length read.
+fn char_ref(s: &str) -> Option<(char, usize)> {
+    let (digits, radix, skip) = match s.strip_prefix("&#x") {
+        Some(hex) => (hex, 16, 3),
+        None => (s.strip_prefix("&#")?, 10, 2),
+    };
+    let len = digits.find(';')?;
+    let digits = &digits[..len];
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    let c = std::char::from_u32(u32::from_str_radix(digits, radix).ok()?)?;
+    Some((c, skip + len + 1)).filter(|_| is_char(c))
+}
+
+/// Normalizes line breaks to LF.
+fn newlines(s: &str) -> String {
+    s.replace("\r\n", "\n").replace('\r', "\n")
+}
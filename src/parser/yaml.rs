@@ -0,0 +1,913 @@
+//! YAML Parser
+use crate::yaml::{boolean, float, int, null, resolve, timestamp};
+use crate::{Error, Map, Parser, Value};
+use std::collections::HashMap;
+use std::iter::once;
+use std::mem::size_of;
+
+/// YAML 1.2 Parser, which resolves plain scalars by the core schema, and
+/// timestamps into `Value::DateTime`.
+///
+/// `parse` accepts a stream of at most one document, where an empty stream is
+/// `null`, and `documents` reads all documents of a stream. Keys of mappings
+/// must be scalars, and merge keys (`<<`) are supported. Collections can be
+/// nested up to 128 levels, and aliases can copy up to 16 MiB of values in
+/// total.
+#[derive(Copy, Clone, Debug)]
+pub struct YamlParser;
+
+impl YamlParser {
+    /// Parses all documents of a stream.
+    pub fn documents(&self, s: &str) -> Result<Vec<Value>, Error> {
+        let documents = Reader::new(s).stream()?;
+        Ok(documents.into_iter().map(|(_, v)| v).collect())
+    }
+}
+
+impl Parser for YamlParser {
+    const FORMAT: &'static str = "YAML";
+
+    fn parse(&self, s: &str) -> Result<Value, Error> {
+        let mut documents = Reader::new(s).stream()?;
+        if let Some((offset, _)) = documents.get(1) {
+            return Err(Error::new("YAML", s, *offset, &["single document"]));
+        }
+        Ok(documents.pop().map_or(Value::Null, |(_, v)| v))
+    }
+}
+
+/// The limit of the total size of values copied by aliases, against aliases
+/// expanding exponentially.
+const EXPANSION_LIMIT: usize = 1 << 24;
+
+/// The limit of nested collections, not to overflow the stack.
+const DEPTH_LIMIT: usize = 128;
+
+/// The approximate size of the value in bytes.
+fn size(value: &Value) -> usize {
+    size_of::<Value>()
+        + match value {
+            Value::String(s) => s.len(),
+            Value::Array(vs) => vs.iter().map(size).sum(),
+            Value::Map(m) => m.iter().map(|(k, v)| k.len() + size(v)).sum(),
+            _ => 0,
+        }
+}
+
+/// Whether `c` separates tokens, being a space, a line break or the end.
+fn is_blank(c: Option<char>) -> bool {
+    matches!(c, None | Some(' ') | Some('\t') | Some('\n') | Some('\r'))
+}
+
+fn is_flow_indicator(c: char) -> bool {
+    matches!(c, ',' | '[' | ']' | '{' | '}')
+}
+
+/// The recursive descent parser over the whole input.
+struct Reader<'a> {
+    input: &'a str,
+    pos: usize,
+    /// Values of anchors with their sizes.
+    anchors: HashMap<String, (Value, usize)>,
+    /// The total size of values copied by aliases.
+    expanded: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            pos: 0,
+            anchors: HashMap::new(),
+            expanded: 0,
+        }
+    }
+
+    fn error(&self, expected: &[&'static str]) -> Error {
+        self.error_at(self.pos, expected)
+    }
+
+    fn error_at(&self, offset: usize, expected: &[&'static str]) -> Error {
+        Error::new("YAML", self.input, offset, expected)
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn peek_nth(&self, n: usize) -> Option<char> {
+        self.rest().chars().nth(n)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn line_start(&self) -> usize {
+        self.input[..self.pos].rfind('\n').map_or(0, |i| i + 1)
+    }
+
+    /// The column counted in characters from 0, to compare indentation.
+    fn column(&self) -> isize {
+        self.input[self.line_start()..self.pos].chars().count() as isize
+    }
+
+    /// Whether only the indentation precedes the position on the line.
+    fn at_line_head(&self) -> bool {
+        self.input[self.line_start()..self.pos]
+            .bytes()
+            .all(|b| b == b' ')
+    }
+
+    fn at_eol(&self) -> bool {
+        let rest = self.rest();
+        rest.is_empty() || rest.starts_with('\n') || rest.starts_with("\r\n")
+    }
+
+    fn skip_spaces(&mut self) {
+        while let Some(' ') | Some('\t') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    /// Skips a line break, returning whether there is one.
+    fn line_break(&mut self) -> bool {
+        let len = if self.rest().starts_with("\r\n") {
+            2
+        } else if self.rest().starts_with('\n') {
+            1
+        } else {
+            0
+        };
+        self.pos += len;
+        len > 0
+    }
+
+    fn skip_line(&mut self) {
+        while !self.at_eol() {
+            self.bump();
+        }
+    }
+
+    /// Skips spaces, comments and line breaks up to the next token, returning
+    /// whether any line break is skipped.
+    fn skip_blanks(&mut self) -> bool {
+        let mut crossed = false;
+        loop {
+            self.skip_spaces();
+            if self.peek() == Some('#') && is_blank(self.input[..self.pos].chars().next_back()) {
+                self.skip_line();
+            }
+            if !self.line_break() {
+                return crossed;
+            }
+            crossed = true;
+        }
+    }
+
+    /// Whether the document marker `---` or `...` is at the position.
+    fn at_marker(&self, marker: &str) -> bool {
+        self.rest().starts_with(marker)
+            && is_blank(self.rest()[marker.len()..].chars().next())
+            && self.column() == 0
+    }
+
+    fn at_document_end(&self) -> bool {
+        self.peek().is_none() || self.at_marker("---") || self.at_marker("...")
+    }
+
+    /// Whether an entry of a block sequence starts at the position.
+    fn at_entry(&self) -> bool {
+        self.peek() == Some('-') && is_blank(self.peek_nth(1))
+    }
+
+    /// Whether an implicit key of a block mapping starts at the position.
+    fn at_key(&self) -> bool {
+        let mut probe = Reader {
+            pos: self.pos,
+            ..Reader::new(self.input)
+        };
+        let key = match probe.peek() {
+            Some('"') | Some('\'') => probe.quoted().is_ok(),
+            _ => !probe.plain_line(false, true).is_empty(),
+        };
+        probe.skip_spaces();
+        key && !self.input[self.pos..probe.pos].contains('\n')
+            && probe.peek() == Some(':')
+            && is_blank(probe.peek_nth(1))
+    }
+
+    fn at_explicit_key(&self) -> bool {
+        self.peek() == Some('?') && is_blank(self.peek_nth(1))
+    }
+
+    /// Reads documents with their offsets.
+    fn stream(&mut self) -> Result<Vec<(usize, Value)>, Error> {
+        let mut documents = Vec::new();
+        loop {
+            self.skip_blanks();
+            // Directives only affect tags, which are not resolved.
+            while self.column() == 0 && self.peek() == Some('%') {
+                self.skip_line();
+                self.skip_blanks();
+            }
+            if self.peek().is_none() {
+                return Ok(documents);
+            }
+
+            let offset = self.pos;
+            if self.at_marker("...") {
+                self.pos += 3;
+                continue;
+            }
+            if self.at_marker("---") {
+                self.pos += 3;
+            }
+            self.anchors.clear();
+            documents.push((offset, self.block_node(-1, false, 0)?));
+
+            self.skip_blanks();
+            if self.at_marker("...") {
+                self.pos += 3;
+            } else if !self.at_document_end() {
+                return Err(self.error(&["end of document"]));
+            }
+        }
+    }
+
+    /// Reads a node of the block context, indented more than `parent`.
+    ///
+    /// `value` is for values of mappings, which can be sequences at the same
+    /// indentation, but can't be collections on the line of the key. `depth` is
+    /// the number of collections containing the node.
+    fn block_node(&mut self, parent: isize, value: bool, depth: usize) -> Result<Value, Error> {
+        let mut anchor = None;
+        let mut tag = None;
+        let mut separated = !value;
+        loop {
+            if self.skip_blanks() {
+                separated = true;
+                let column = self.column();
+                if self.at_document_end()
+                    || column < parent
+                    || column == parent && !(value && self.at_entry())
+                {
+                    return self.empty(anchor, tag);
+                }
+            } else if self.peek().is_none() {
+                return self.empty(anchor, tag);
+            }
+            match self.peek() {
+                Some('&') => anchor = Some(self.anchor()?),
+                Some('!') => tag = Some(self.tag()?),
+                _ => break,
+            }
+        }
+
+        let start = self.pos;
+        let column = self.column();
+        let tag = tag.as_deref();
+        let node = match self.peek() {
+            Some('*') => self.alias()?,
+            Some('-') | Some('?') if is_blank(self.peek_nth(1)) && !separated => {
+                return Err(self.error(&["line break"]))
+            }
+            Some('-') if self.at_entry() => self.block_sequence(column, depth)?,
+            Some('|') | Some('>') => {
+                let text = self.block_scalar(parent)?;
+                self.scalar(start, tag, text, false)?
+            }
+            _ if self.at_key() || self.at_explicit_key() => {
+                if !separated {
+                    return Err(self.error(&["line break"]));
+                }
+                self.block_mapping(column, depth)?
+            }
+            Some('[') | Some('{') => self.flow_collection(depth)?,
+            Some('"') | Some('\'') => {
+                let text = self.quoted()?;
+                self.scalar(start, tag, text, false)?
+            }
+            _ => {
+                let text = self.plain(parent, false)?;
+                self.scalar(start, tag, text, true)?
+            }
+        };
+        Ok(self.node(anchor, node))
+    }
+
+    /// Makes an empty node, which is `null` unless tagged otherwise.
+    fn empty(&mut self, anchor: Option<String>, tag: Option<String>) -> Result<Value, Error> {
+        let node = self.scalar(self.pos, tag.as_deref(), String::new(), true)?;
+        Ok(self.node(anchor, node))
+    }
+
+    /// Registers the node to the anchor if any.
+    fn node(&mut self, anchor: Option<String>, node: Value) -> Value {
+        if let Some(anchor) = anchor {
+            self.anchors.insert(anchor, (node.clone(), size(&node)));
+        }
+        node
+    }
+
+    fn block_sequence(&mut self, column: isize, depth: usize) -> Result<Value, Error> {
+        if depth >= DEPTH_LIMIT {
+            return Err(self.error(&["nesting within limits"]));
+        }
+        let mut seq = Vec::new();
+        loop {
+            self.bump();
+            seq.push(self.block_node(column, false, depth + 1)?);
+
+            self.skip_blanks();
+            if self.at_document_end() || self.column() < column {
+                break;
+            }
+            if !self.at_line_head() {
+                return Err(self.error(&["line break"]));
+            }
+            if self.column() > column {
+                return Err(self.error(&["sequence entry"]));
+            }
+            // Otherwise a key of the parent mapping.
+            if !self.at_entry() {
+                break;
+            }
+        }
+        Ok(Value::Array(seq))
+    }
+
+    fn block_mapping(&mut self, column: isize, depth: usize) -> Result<Value, Error> {
+        if depth >= DEPTH_LIMIT {
+            return Err(self.error(&["nesting within limits"]));
+        }
+        let mut map = Map::new();
+        let mut merges = Vec::new();
+        loop {
+            let explicit = self.at_explicit_key();
+            if explicit {
+                self.bump();
+                self.skip_spaces();
+            }
+            let start = self.pos;
+            let (key, plain) = match self.peek() {
+                Some('"') | Some('\'') => (self.quoted()?, false),
+                _ => (self.plain_line(false, true).to_string(), true),
+            };
+            // Only scalars on the line can be explicit keys.
+            if explicit && plain && key.is_empty() {
+                return Err(self.error(&["scalar key"]));
+            }
+
+            let value = if !explicit {
+                self.skip_spaces();
+                self.bump();
+                self.block_node(column, true, depth + 1)?
+            } else if self.skip_blanks()
+                && self.column() == column
+                && self.peek() == Some(':')
+                && is_blank(self.peek_nth(1))
+            {
+                // Like entries of sequences, the value can be a compact collection.
+                self.bump();
+                self.block_node(column, false, depth + 1)?
+            } else {
+                Value::Null
+            };
+            if plain && key == "<<" {
+                merges.push((start, value));
+            } else if map.insert(key, value).is_some() {
+                return Err(self.error_at(start, &["unique key"]));
+            }
+
+            self.skip_blanks();
+            if self.at_document_end() || self.column() < column {
+                break;
+            }
+            if !self.at_line_head() {
+                return Err(self.error(&["line break"]));
+            }
+            if self.column() > column || !(self.at_key() || self.at_explicit_key()) {
+                return Err(self.error(&["mapping key"]));
+            }
+        }
+
+        Ok(Value::Map(self.merge(map, merges)?))
+    }
+
+    /// Merges mappings of merge keys with their offsets into `map`.
+    fn merge(&self, map: Map, merges: Vec<(usize, Value)>) -> Result<Map, Error> {
+        if merges.is_empty() {
+            return Ok(map);
+        }
+        // Merged keys come first, and are overridden by the mapping itself.
+        let mut merged = Map::new();
+        for (start, value) in merges {
+            let sources = match value {
+                Value::Array(vs) => vs,
+                v => vec![v],
+            };
+            for source in sources {
+                match source {
+                    Value::Map(m) => {
+                        for (k, v) in m {
+                            merged.entry(k).or_insert(v);
+                        }
+                    }
+                    _ => return Err(self.error_at(start, &["mapping to merge"])),
+                }
+            }
+        }
+        merged.extend(map);
+        Ok(merged)
+    }
+
+    /// Reads a literal or folded block scalar, whose content is indented more
+    /// than `parent`.
+    fn block_scalar(&mut self, parent: isize) -> Result<String, Error> {
+        let folded = self.bump() == Some('>');
+        let mut chomping = None;
+        let mut indent = None;
+        for _ in 0..2 {
+            match self.peek() {
+                Some(c @ '-') | Some(c @ '+') if chomping.is_none() => chomping = Some(c),
+                Some(c @ '1'..='9') if indent.is_none() => {
+                    indent = Some(parent.max(0) + c.to_digit(10).unwrap() as isize)
+                }
+                _ => break,
+            }
+            self.bump();
+        }
+        self.skip_spaces();
+        if self.peek() == Some('#') && is_blank(self.input[..self.pos].chars().next_back()) {
+            self.skip_line();
+        }
+        if !self.at_eol() {
+            return Err(self.error(&["line break"]));
+        }
+        self.line_break();
+
+        // Detect the indentation by the first non-empty line.
+        let indent = indent.unwrap_or_else(|| {
+            let spaces = self
+                .rest()
+                .split('\n')
+                .map(|l| l.trim_end_matches('\r'))
+                .find(|l| !l.trim_start_matches(' ').is_empty())
+                .map_or(0, |l| l.len() - l.trim_start_matches(' ').len());
+            (spaces as isize).max(parent + 1)
+        });
+
+        let mut lines = Vec::new();
+        let mut ended = false;
+        while self.peek().is_some() && !(indent == 0 && self.at_document_end()) {
+            let len = self.rest().find('\n').unwrap_or_else(|| self.rest().len());
+            let line = self.rest()[..len].trim_end_matches('\r');
+            let spaces = line.len() - line.trim_start_matches(' ').len();
+            if (spaces as isize) < indent && spaces < line.len() {
+                break;
+            }
+            lines.push(line.get(indent as usize..).unwrap_or(""));
+            self.pos += len;
+            ended = self.line_break();
+        }
+
+        let trailing = lines.iter().rev().take_while(|l| l.is_empty()).count();
+        let body = &lines[..lines.len() - trailing];
+        let mut text = if folded { fold(body) } else { body.join("\n") };
+        let breaks = match chomping {
+            Some('-') => 0,
+            Some(_) if body.is_empty() => trailing,
+            Some(_) => trailing + 1,
+            None if body.is_empty() || trailing == 0 && !ended => 0,
+            None => 1,
+        };
+        text.extend((0..breaks).map(|_| '\n'));
+        Ok(text)
+    }
+
+    /// Reads a single or double quoted scalar.
+    fn quoted(&mut self) -> Result<String, Error> {
+        let quote = self.bump().unwrap();
+        let mut text = String::new();
+        // The length without trailing spaces, which are trimmed at line breaks.
+        let mut content = 0;
+        loop {
+            if self.at_eol() && self.peek().is_some() {
+                self.line_break();
+                text.truncate(content);
+                let mut breaks = 0;
+                loop {
+                    self.skip_spaces();
+                    if !self.line_break() {
+                        break;
+                    }
+                    breaks += 1;
+                }
+                if breaks == 0 {
+                    text.push(' ');
+                } else {
+                    text.extend((0..breaks).map(|_| '\n'));
+                }
+                content = text.len();
+                continue;
+            }
+            match self.bump() {
+                None if quote == '"' => return Err(self.error(&["\"\\\"\""])),
+                None => return Err(self.error(&["\"'\""])),
+                Some('\'') if quote == '\'' && self.peek() == Some('\'') => {
+                    self.bump();
+                    text.push('\'');
+                }
+                Some(c) if c == quote => return Ok(text),
+                Some('\\') if quote == '"' => {
+                    // An escaped line break joins the lines without spaces.
+                    if self.line_break() {
+                        self.skip_spaces();
+                    } else {
+                        text.push(self.escape()?);
+                    }
+                }
+                Some(c) => {
+                    text.push(c);
+                    if c == ' ' || c == '\t' {
+                        continue;
+                    }
+                }
+            }
+            content = text.len();
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, Error> {
+        let c = match self.peek() {
+            Some('0') => '\0',
+            Some('a') => '\x07',
+            Some('b') => '\x08',
+            Some('t') | Some('\t') => '\t',
+            Some('n') => '\n',
+            Some('v') => '\x0b',
+            Some('f') => '\x0c',
+            Some('r') => '\r',
+            Some('e') => '\x1b',
+            Some(c @ ' ') | Some(c @ '"') | Some(c @ '/') | Some(c @ '\\') => c,
+            Some('N') => '\u{85}',
+            Some('_') => '\u{a0}',
+            Some('L') => '\u{2028}',
+            Some('P') => '\u{2029}',
+            Some('x') => return self.unicode(2),
+            Some('u') => return self.unicode(4),
+            Some('U') => return self.unicode(8),
+            _ => return Err(self.error(&["escape"])),
+        };
+        self.bump();
+        Ok(c)
+    }
+
+    /// Reads a code point of `digits` hexadecimal digits after the escape
+    /// character, combining surrogate pairs.
+    fn unicode(&mut self, digits: usize) -> Result<char, Error> {
+        let start = self.pos - 1;
+        self.bump();
+        let mut code = self.hex(digits)?;
+        if digits == 4 && (0xd800..0xdc00).contains(&code) && self.rest().starts_with("\\u") {
+            let high = self.pos;
+            self.pos += 2;
+            match self.hex(4)? {
+                low @ 0xdc00..=0xdfff => code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00),
+                _ => self.pos = high,
+            }
+        }
+        std::char::from_u32(code).ok_or_else(|| self.error_at(start, &["Unicode scalar value"]))
+    }
+
+    fn hex(&mut self, digits: usize) -> Result<u32, Error> {
+        let hex = self
+            .rest()
+            .get(..digits)
+            .filter(|h| h.bytes().all(|b| b.is_ascii_hexdigit()))
+            .ok_or_else(|| self.error(&["hexadecimal digit"]))?;
+        self.pos += digits;
+        Ok(u32::from_str_radix(hex, 16).unwrap())
+    }
+
+    /// Reads the part of a plain scalar on the current line, without trailing
+    /// spaces. Returns an empty string if the scalar can't start at the
+    /// position, when it is the `first` line.
+    fn plain_line(&mut self, flow: bool, first: bool) -> &'a str {
+        let start = self.pos;
+        match self.peek() {
+            Some('-') | Some('?') | Some(':') if first => {
+                let next = self.peek_nth(1);
+                if is_blank(next) || flow && next.is_some_and(is_flow_indicator) {
+                    return "";
+                }
+            }
+            Some(c) if first && ("#&*!|>'\"%@`".contains(c) || is_flow_indicator(c)) => return "",
+            _ => {}
+        }
+
+        let mut end = start;
+        while let Some(c) = self.peek() {
+            let next = self.peek_nth(1);
+            let stop = match c {
+                '\n' => true,
+                '\r' => next == Some('\n'),
+                ':' => is_blank(next) || flow && next.is_some_and(is_flow_indicator),
+                '#' => is_blank(self.input[..self.pos].chars().next_back()),
+                c => flow && is_flow_indicator(c),
+            };
+            if stop {
+                break;
+            }
+            self.bump();
+            if c != ' ' && c != '\t' {
+                end = self.pos;
+            }
+        }
+        self.pos = end;
+        &self.input[start..end]
+    }
+
+    /// Reads a plain scalar, which continues to lines indented more than
+    /// `parent` in the block context.
+    fn plain(&mut self, parent: isize, flow: bool) -> Result<String, Error> {
+        let mut text = self.plain_line(flow, true).to_string();
+        if text.is_empty() {
+            return Err(self.error(&["node"]));
+        }
+        loop {
+            let save = self.pos;
+            let mut breaks = 0;
+            loop {
+                self.skip_spaces();
+                if !self.line_break() {
+                    break;
+                }
+                breaks += 1;
+            }
+            let line = if breaks == 0
+                || self.at_document_end()
+                || !flow && self.column() <= parent
+                || self.peek() == Some('#')
+            {
+                ""
+            } else {
+                self.plain_line(flow, false)
+            };
+            // Implicit keys can't span lines.
+            if line.is_empty() || self.peek() == Some(':') && !flow {
+                self.pos = save;
+                return Ok(text);
+            }
+            if breaks == 1 {
+                text.push(' ');
+            } else {
+                text.extend((1..breaks).map(|_| '\n'));
+            }
+            text.push_str(line);
+        }
+    }
+
+    /// Reads a flow sequence or a flow mapping.
+    fn flow_collection(&mut self, depth: usize) -> Result<Value, Error> {
+        if depth >= DEPTH_LIMIT {
+            return Err(self.error(&["nesting within limits"]));
+        }
+        let sequence = self.bump() == Some('[');
+        let (close, expected) = if sequence {
+            (']', ["\",\"", "\"]\""])
+        } else {
+            ('}', ["\",\"", "\"}\""])
+        };
+        let mut seq = Vec::new();
+        let mut map = Map::new();
+        let mut merges = Vec::new();
+        loop {
+            self.skip_blanks();
+            match self.peek() {
+                Some(c) if c == close => {
+                    self.bump();
+                    break;
+                }
+                None => return Err(self.error(&expected[1..])),
+                _ => {}
+            }
+
+            let start = self.pos;
+            match self.flow_entry(sequence, depth + 1)? {
+                (None, value) if sequence => seq.push(value),
+                (Some((key, _)), value) if sequence => {
+                    seq.push(Value::Map(once((key, value)).collect()))
+                }
+                (Some((key, true)), value) if key == "<<" => merges.push((start, value)),
+                (Some((key, _)), value) => {
+                    if map.insert(key, value).is_some() {
+                        return Err(self.error_at(start, &["unique key"]));
+                    }
+                }
+                (None, _) => return Err(self.error_at(start, &["mapping key"])),
+            }
+
+            self.skip_blanks();
+            match self.peek() {
+                Some(',') => {}
+                Some(c) if c == close => {
+                    self.bump();
+                    break;
+                }
+                _ => return Err(self.error(&expected)),
+            }
+            self.bump();
+        }
+        Ok(if sequence {
+            Value::Array(seq)
+        } else {
+            Value::Map(self.merge(map, merges)?)
+        })
+    }
+
+    /// Reads an entry of a flow collection, with its key and whether it is plain
+    /// if the entry is a pair.
+    fn flow_entry(
+        &mut self,
+        sequence: bool,
+        depth: usize,
+    ) -> Result<(Option<(String, bool)>, Value), Error> {
+        if self.at_explicit_key() {
+            self.bump();
+            self.skip_blanks();
+        }
+        let start = self.pos;
+        let (text, plain) = match self.peek() {
+            Some('"') | Some('\'') => (self.quoted()?, false),
+            Some('[') | Some('{') | Some('*') | Some('&') | Some('!') => {
+                return Ok((None, self.flow_node(depth)?))
+            }
+            _ => (self.plain(-1, true)?, true),
+        };
+
+        self.skip_blanks();
+        let next = self.peek_nth(1);
+        if self.peek() == Some(':')
+            && (!plain || is_blank(next) || next.is_some_and(is_flow_indicator))
+        {
+            self.bump();
+            self.skip_blanks();
+            let value = match self.peek() {
+                Some(',') | Some(']') | Some('}') => Value::Null,
+                _ => self.flow_node(depth)?,
+            };
+            Ok((Some((text, plain)), value))
+        } else if sequence {
+            Ok((None, self.scalar(start, None, text, plain)?))
+        } else {
+            Ok((Some((text, plain)), Value::Null))
+        }
+    }
+
+    fn flow_node(&mut self, depth: usize) -> Result<Value, Error> {
+        let mut anchor = None;
+        let mut tag = None;
+        loop {
+            self.skip_blanks();
+            match self.peek() {
+                Some('&') => anchor = Some(self.anchor()?),
+                Some('!') => tag = Some(self.tag()?),
+                _ => break,
+            }
+        }
+
+        let start = self.pos;
+        let tag = tag.as_deref();
+        let node = match self.peek() {
+            Some('[') | Some('{') => self.flow_collection(depth)?,
+            Some('*') => self.alias()?,
+            Some(',') | Some(']') | Some('}') if anchor.is_some() || tag.is_some() => {
+                self.scalar(start, tag, String::new(), true)?
+            }
+            Some('"') | Some('\'') => {
+                let text = self.quoted()?;
+                self.scalar(start, tag, text, false)?
+            }
+            _ => {
+                let text = self.plain(-1, true)?;
+                self.scalar(start, tag, text, true)?
+            }
+        };
+        Ok(self.node(anchor, node))
+    }
+
+    /// Reads the name of an anchor, an alias or a tag.
+    fn name(&mut self) -> &'a str {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if is_blank(Some(c)) || is_flow_indicator(c) {
+                break;
+            }
+            self.bump();
+        }
+        &self.input[start..self.pos]
+    }
+
+    fn anchor(&mut self) -> Result<String, Error> {
+        self.bump();
+        match self.name() {
+            "" => Err(self.error(&["anchor name"])),
+            name => Ok(name.to_string()),
+        }
+    }
+
+    fn alias(&mut self) -> Result<Value, Error> {
+        let start = self.pos;
+        self.bump();
+        let name = self.name();
+        let (value, size) = match self.anchors.get(name) {
+            Some(anchor) => anchor,
+            None => return Err(self.error_at(start, &["defined anchor"])),
+        };
+        self.expanded += size;
+        if self.expanded > EXPANSION_LIMIT {
+            return Err(self.error_at(start, &["alias expansion within limits"]));
+        }
+        Ok(value.clone())
+    }
+
+    /// Reads a tag, in the shorthand form like `!!str` for the core schema.
+    fn tag(&mut self) -> Result<String, Error> {
+        if !self.rest().starts_with("!<") {
+            return Ok(self.name().to_string());
+        }
+        let end = self
+            .rest()
+            .find('>')
+            .ok_or_else(|| self.error(&["\">\""]))?;
+        let uri = &self.rest()[2..end];
+        self.pos += end + 1;
+        Ok(match uri.strip_prefix("tag:yaml.org,2002:") {
+            Some(name) => format!("!!{}", name),
+            None => uri.to_string(),
+        })
+    }
+
+    /// Makes a scalar from `text`, resolving its type by the tag, or by the
+    /// schema if it is plain and not tagged.
+    fn scalar(
+        &self,
+        start: usize,
+        tag: Option<&str>,
+        text: String,
+        plain: bool,
+    ) -> Result<Value, Error> {
+        let (value, expected) = match tag {
+            None if plain => return Ok(resolve(text)),
+            Some("!!null") => (null(&text), "null"),
+            Some("!!bool") => (boolean(&text), "boolean"),
+            Some("!!int") => (int(&text), "integer"),
+            Some("!!float") => (
+                float(&text).or_else(|| match int(&text) {
+                    Some(Value::Int(i)) => Some(Value::Float(i as f64)),
+                    _ => None,
+                }),
+                "float",
+            ),
+            Some("!!timestamp") => (timestamp(&text).map(Value::DateTime), "timestamp"),
+            // Other tags including `!` and `!!str` are kept as strings.
+            _ => return Ok(Value::String(text)),
+        };
+        value.ok_or_else(|| self.error_at(start, &[expected]))
+    }
+}
+
+/// Folds lines of a folded block scalar, except for more indented ones.
+fn fold(lines: &[&str]) -> String {
+    let spaced = |l: &str| l.starts_with(' ') || l.starts_with('\t');
+    let mut text = String::new();
+    let mut previous: Option<&str> = None;
+    let mut empty = 0;
+    for line in lines {
+        if line.is_empty() {
+            empty += 1;
+            continue;
+        }
+        let breaks = match previous {
+            Some(p) if spaced(p) || spaced(line) => empty + 1,
+            Some(_) if empty == 0 => {
+                text.push(' ');
+                0
+            }
+            _ => empty,
+        };
+        text.extend((0..breaks).map(|_| '\n'));
+        text.push_str(line);
+        previous = Some(line);
+        empty = 0;
+    }
+    text
+}
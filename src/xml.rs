@@ -0,0 +1,32 @@
+//! Names and Characters of XML
+
+/// Whether the character is allowed in XML 1.0 documents.
+pub(crate) fn is_char(c: char) -> bool {
+    !matches!(c, '\0'..='\u{8}' | '\u{b}' | '\u{c}' | '\u{e}'..='\u{1f}' | '\u{fffe}' | '\u{ffff}')
+}
+
+/// Whether the character can start names.
+pub(crate) fn is_name_start(c: char) -> bool {
+    matches!(c,
+        ':' | 'A'..='Z' | '_' | 'a'..='z' | '\u{c0}'..='\u{d6}' | '\u{d8}'..='\u{f6}'
+        | '\u{f8}'..='\u{2ff}' | '\u{370}'..='\u{37d}' | '\u{37f}'..='\u{1fff}'
+        | '\u{200c}'..='\u{200d}' | '\u{2070}'..='\u{218f}' | '\u{2c00}'..='\u{2fef}'
+        | '\u{3001}'..='\u{d7ff}' | '\u{f900}'..='\u{fdcf}' | '\u{fdf0}'..='\u{fffd}'
+        | '\u{10000}'..='\u{effff}')
+}
+
+/// Whether the character can be in names.
+pub(crate) fn is_name_char(c: char) -> bool {
+    is_name_start(c)
+        || matches!(c,
+            '-' | '.' | '0'..='9' | '\u{b7}' | '\u{300}'..='\u{36f}' | '\u{203f}'..='\u{2040}')
+}
+
+/// Whether the name is a qualified name of namespaces, like `soap:Body`.
+pub(crate) fn is_qualified_name(name: &str) -> bool {
+    let mut parts = name.split(':');
+    let valid = |part: Option<&str>| {
+        part.is_some_and(|p| p.starts_with(is_name_start) && p.chars().all(is_name_char))
+    };
+    valid(parts.next()) && parts.clone().count() <= 1 && parts.all(|p| valid(Some(p)))
+}
@@ -0,0 +1,238 @@
+//! Scalars of YAML
+//!
+//! Plain scalars are resolved by the core schema of YAML 1.2, and timestamps of
+//! YAML 1.1. Strings are written as plain scalars only if they are read back as
+//! strings by both YAML 1.1 and 1.2, and double quoted otherwise.
+use crate::datetime::*;
+use crate::Value;
+
+/// Resolves the type of a plain scalar by the core schema, with timestamps.
+pub(crate) fn resolve(text: String) -> Value {
+    null(&text)
+        .or_else(|| boolean(&text))
+        .or_else(|| int(&text))
+        .or_else(|| float(&text))
+        .or_else(|| timestamp(&text).map(Value::DateTime))
+        .unwrap_or(Value::String(text))
+}
+
+pub(crate) fn null(text: &str) -> Option<Value> {
+    match text {
+        "" | "~" | "null" | "Null" | "NULL" => Some(Value::Null),
+        _ => None,
+    }
+}
+
+pub(crate) fn boolean(text: &str) -> Option<Value> {
+    match text {
+        "true" | "True" | "TRUE" => Some(Value::Boolean(true)),
+        "false" | "False" | "FALSE" => Some(Value::Boolean(false)),
+        _ => None,
+    }
+}
+
+fn is_digits(s: &str, radix: u32) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_digit(radix))
+}
+
+pub(crate) fn int(text: &str) -> Option<Value> {
+    let int = if let Some(oct) = text.strip_prefix("0o") {
+        i128::from_str_radix(oct, 8)
+            .ok()
+            .filter(|_| is_digits(oct, 8))
+    } else if let Some(hex) = text.strip_prefix("0x") {
+        i128::from_str_radix(hex, 16)
+            .ok()
+            .filter(|_| is_digits(hex, 16))
+    } else {
+        let digits = text.strip_prefix(&['-', '+'][..]).unwrap_or(text);
+        text.parse().ok().filter(|_| is_digits(digits, 10))
+    };
+    int.map(Value::Int)
+}
+
+pub(crate) fn float(text: &str) -> Option<Value> {
+    let unsigned = text.strip_prefix(&['-', '+'][..]).unwrap_or(text);
+    let float = match unsigned {
+        ".nan" | ".NaN" | ".NAN" if unsigned == text => f64::NAN,
+        ".inf" | ".Inf" | ".INF" => f64::INFINITY,
+        _ => {
+            let (mantissa, exponent) = match unsigned.find(&['e', 'E'][..]) {
+                Some(i) => (&unsigned[..i], Some(&unsigned[i + 1..])),
+                None => (unsigned, None),
+            };
+            let (int, fraction) = match mantissa.find('.') {
+                Some(i) => (&mantissa[..i], &mantissa[i + 1..]),
+                None => (mantissa, ""),
+            };
+            let valid = (is_digits(int, 10) || is_digits(fraction, 10))
+                && int
+                    .bytes()
+                    .chain(fraction.bytes())
+                    .all(|b| b.is_ascii_digit())
+                && exponent.map_or(true, |e| {
+                    is_digits(e.strip_prefix(&['-', '+'][..]).unwrap_or(e), 10)
+                });
+            if !valid {
+                return None;
+            }
+            unsigned.parse().ok()?
+        }
+    };
+    Some(Value::Float(if text.starts_with('-') {
+        -float
+    } else {
+        float
+    }))
+}
+
+/// Takes from `min` to `max` ASCII digits from the head of `s`.
+fn digits<'a>(s: &mut &'a str, min: usize, max: usize) -> Option<&'a str> {
+    let len = s.bytes().take(max).take_while(u8::is_ascii_digit).count();
+    if len < min {
+        return None;
+    }
+    let (digits, rest) = s.split_at(len);
+    *s = rest;
+    Some(digits)
+}
+
+/// Parses a timestamp of YAML 1.1, like `2001-12-14 21:59:43.10 -5`.
+pub(crate) fn timestamp(text: &str) -> Option<DateTime> {
+    let mut s = text;
+    let year = digits(&mut s, 4, 4)?.parse().ok()?;
+    s = s.strip_prefix('-')?;
+    let month = digits(&mut s, 1, 2)?;
+    s = s.strip_prefix('-')?;
+    let day = digits(&mut s, 1, 2)?;
+    let date = Date::new(year, month.parse().ok()?, day.parse().ok()?).ok()?;
+    if s.is_empty() {
+        return if month.len() == 2 && day.len() == 2 {
+            DateTime::new(Some(date), None, None).ok()
+        } else {
+            None
+        };
+    }
+
+    let separated = s.trim_start_matches(&[' ', '\t'][..]);
+    s = match s.strip_prefix(&['T', 't'][..]) {
+        Some(rest) => rest,
+        None if separated.len() < s.len() => separated,
+        None => return None,
+    };
+    let hour = digits(&mut s, 1, 2)?.parse().ok()?;
+    s = s.strip_prefix(':')?;
+    let minute = digits(&mut s, 2, 2)?.parse().ok()?;
+    s = s.strip_prefix(':')?;
+    let second = digits(&mut s, 2, 2)?.parse().ok()?;
+    let mut nanosecond = 0;
+    if let Some(rest) = s.strip_prefix('.') {
+        s = rest;
+        let fraction = digits(&mut s, 0, usize::MAX)?;
+        nanosecond = format!("{:0<9.9}", fraction).parse().ok()?;
+    }
+    let time = Time::new(hour, minute, second, nanosecond).ok()?;
+
+    s = s.trim_start_matches(&[' ', '\t'][..]);
+    let offset = if s.is_empty() {
+        None
+    } else if s == "Z" {
+        Some(Offset::new(0, 0).ok()?)
+    } else {
+        let sign = match s.strip_prefix('-') {
+            Some(rest) => {
+                s = rest;
+                -1
+            }
+            None => {
+                s = s.strip_prefix('+')?;
+                1
+            }
+        };
+        let hour = digits(&mut s, 1, 2)?.parse::<i8>().ok()?;
+        let minute = match s.strip_prefix(':') {
+            Some(rest) => {
+                s = rest;
+                digits(&mut s, 2, 2)?.parse().ok()?
+            }
+            None => 0,
+        };
+        if !s.is_empty() {
+            return None;
+        }
+        Some(Offset::new(sign * hour, minute).ok()?)
+    };
+    DateTime::new(Some(date), Some(time), offset).ok()
+}
+
+/// Whether the string can be written as a plain scalar in the block context.
+pub(crate) fn is_plain(s: &str) -> bool {
+    let mut chars = s.chars();
+    let starts_safely = match (chars.next(), chars.next()) {
+        (None, _) => false,
+        // Indicators followed by spaces start collections.
+        (Some('-'), Some(c)) | (Some('?'), Some(c)) | (Some(':'), Some(c)) => c != ' ',
+        (Some(c), _) => !"-?:,[]{}#&*!|>'\"%@` ".contains(c),
+    };
+    // Numbers of YAML 1.1, like `1_000`, `0b11` or `1:30`.
+    let digits = s.trim_start_matches(&['-', '+'][..]);
+    let numeric = digits.starts_with(|c: char| c.is_ascii_digit() || c == '.');
+    // Booleans of YAML 1.1, and keys for merging or default values.
+    let reserved = matches!(
+        s,
+        "y" | "Y"
+            | "yes"
+            | "Yes"
+            | "YES"
+            | "n"
+            | "N"
+            | "no"
+            | "No"
+            | "NO"
+            | "on"
+            | "On"
+            | "ON"
+            | "off"
+            | "Off"
+            | "OFF"
+            | "<<"
+            | "="
+    );
+
+    starts_safely
+        && !numeric
+        && !reserved
+        && !s.starts_with("---")
+        && !s.ends_with(' ')
+        && !s.ends_with(':')
+        && !s.contains(": ")
+        && !s.contains(" #")
+        && !s
+            .chars()
+            .any(|c| c.is_control() || matches!(c, '\u{2028}' | '\u{2029}' | '\u{feff}'))
+        && matches!(resolve(s.to_string()), Value::String(_))
+}
+
+/// Double quotes and escapes the string.
+pub(crate) fn quoted(s: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\0' => quoted.push_str("\\0"),
+            '\t' => quoted.push_str("\\t"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\x1b' => quoted.push_str("\\e"),
+            '\u{85}' => quoted.push_str("\\N"),
+            '\u{2028}' => quoted.push_str("\\L"),
+            '\u{2029}' => quoted.push_str("\\P"),
+            c if c.is_control() => quoted.push_str(&format!("\\x{:02X}", c as u32)),
+            '\u{feff}' => quoted.push_str("\\uFEFF"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
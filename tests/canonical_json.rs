@@ -0,0 +1,130 @@
+//! Tests of `CanonicalJsonGenerator` with the examples of RFC 8785.
+mod common;
+
+use common::{generate, map, string};
+use df_utils::generator::CanonicalJsonGenerator;
+use df_utils::parser::JsonParser;
+use df_utils::{Generator, Parser, Value};
+
+fn canonical(json: &str) -> String {
+    generate(&CanonicalJsonGenerator, &JsonParser.parse(json).unwrap())
+}
+
+#[test]
+fn example() {
+    // RFC 8785, Section 3.2.2
+    let json = r#"{
+      "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+      "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+      "literals": [null, true, false]
+    }"#;
+    assert_eq!(
+        canonical(json),
+        r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#
+    );
+}
+
+#[test]
+fn numbers() {
+    // RFC 8785, Appendix B
+    for (bits, expected) in &[
+        (0x0000000000000000u64, "0"),
+        (0x8000000000000000, "0"),
+        (0x0000000000000001, "5e-324"),
+        (0x8000000000000001, "-5e-324"),
+        (0x7fefffffffffffff, "1.7976931348623157e+308"),
+        (0xffefffffffffffff, "-1.7976931348623157e+308"),
+        (0x4340000000000000, "9007199254740992"),
+        (0xc340000000000000, "-9007199254740992"),
+        (0x4430000000000000, "295147905179352830000"),
+        (0x44b52d02c7e14af5, "9.999999999999997e+22"),
+        (0x44b52d02c7e14af6, "1e+23"),
+        (0x44b52d02c7e14af7, "1.0000000000000001e+23"),
+        (0x444b1ae4d6e2ef4e, "999999999999999700000"),
+        (0x444b1ae4d6e2ef4f, "999999999999999900000"),
+        (0x444b1ae4d6e2ef50, "1e+21"),
+        (0x3eb0c6f7a0b5ed8c, "9.999999999999997e-7"),
+        (0x3eb0c6f7a0b5ed8d, "0.000001"),
+        (0x41b3de4355555553, "333333333.3333332"),
+        (0x41b3de4355555554, "333333333.33333325"),
+        (0x41b3de4355555555, "333333333.3333333"),
+        (0x41b3de4355555556, "333333333.3333334"),
+        (0x41b3de4355555557, "333333333.33333343"),
+        (0xbecbf647612f3696, "-0.0000033333333333333333"),
+        (0x43143ff3c1cb0959, "1424953923781206.2"),
+    ] {
+        let f = f64::from_bits(*bits);
+        assert_eq!(
+            generate(&CanonicalJsonGenerator, &Value::Float(f)),
+            *expected,
+            "{:016x}",
+            bits
+        );
+    }
+
+    for (value, expected) in &[
+        (Value::Float(1e-7), "1e-7"),
+        (Value::Float(1e21), "1e+21"),
+        (Value::Float(1e20), "100000000000000000000"),
+        (Value::Float(-0.0), "0"),
+        (Value::Float(0.5), "0.5"),
+        (Value::Int(-42), "-42"),
+        (Value::Int(i64::MAX.into()), "9223372036854776000"),
+        (Value::Int(u64::MAX.into()), "18446744073709552000"),
+    ] {
+        assert_eq!(generate(&CanonicalJsonGenerator, value), *expected);
+    }
+
+    for f in &[f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        let value = Value::Array(vec![Value::Int(1), Value::Float(*f)]);
+        let mut buf = Vec::new();
+        let e = CanonicalJsonGenerator
+            .generate(&mut buf, &value)
+            .unwrap_err();
+        assert_eq!(e.kind(), std::io::ErrorKind::InvalidData);
+        assert!(buf.is_empty());
+    }
+}
+
+#[test]
+fn sorting() {
+    // RFC 8785, Section 3.2.3
+    let json = r#"{
+      "\u20ac": "Euro Sign",
+      "\r": "Carriage Return",
+      "\ufb33": "Hebrew Letter Dalet With Dagesh",
+      "1": "One",
+      "\ud83d\ude00": "Emoji: Grinning Face",
+      "\u0080": "Control",
+      "\u00f6": "Latin Small Letter O With Diaeresis"
+    }"#;
+    assert_eq!(
+        canonical(json),
+        "{\"\\r\":\"Carriage Return\",\
+         \"1\":\"One\",\
+         \"\u{80}\":\"Control\",\
+         \"ö\":\"Latin Small Letter O With Diaeresis\",\
+         \"€\":\"Euro Sign\",\
+         \"😀\":\"Emoji: Grinning Face\",\
+         \"\u{fb33}\":\"Hebrew Letter Dalet With Dagesh\"}"
+    );
+
+    // Nested maps are sorted too, while arrays keep their order.
+    let value = map(vec![
+        ("b", Value::Array(vec![string("z"), string("a")])),
+        ("a", map(vec![("y", Value::Null), ("x", Value::Null)])),
+    ]);
+    assert_eq!(
+        generate(&CanonicalJsonGenerator, &value),
+        r#"{"a":{"x":null,"y":null},"b":["z","a"]}"#
+    );
+}
+
+#[test]
+fn strings() {
+    let value = string("\"\\/\u{8}\u{c}\n\r\t\u{0}\u{1f}\u{7f}\u{2028}é😀");
+    assert_eq!(
+        generate(&CanonicalJsonGenerator, &value),
+        "\"\\\"\\\\/\\b\\f\\n\\r\\t\\u0000\\u001f\u{7f}\u{2028}é😀\""
+    );
+}
@@ -0,0 +1,131 @@
+//! Helpers shared by the integration tests, each of which uses a part of them.
+#![allow(dead_code)]
+use df_utils::parser::TomlParser;
+use df_utils::{Generator, Map, Parser, Value};
+
+/// Compares values strictly, including the order of keys and NaNs.
+pub fn same(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Null, Value::Null) => true,
+        (Value::Int(a), Value::Int(b)) => a == b,
+        (Value::Float(a), Value::Float(b)) => {
+            a.to_bits() == b.to_bits() || a.is_nan() && b.is_nan()
+        }
+        (Value::Boolean(a), Value::Boolean(b)) => a == b,
+        (Value::String(a), Value::String(b)) => a == b,
+        (Value::DateTime(a), Value::DateTime(b)) => a == b,
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same(a, b))
+        }
+        (Value::Map(a), Value::Map(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .zip(b)
+                    .all(|((ka, va), (kb, vb))| ka == kb && same(va, vb))
+        }
+        _ => false,
+    }
+}
+
+/// Asserts `same`, showing `context` like the text being parsed on failures.
+pub fn assert_same(a: &Value, b: &Value, context: &str) {
+    assert!(same(a, b), "{:?}\n{:?}\n{}", a, b, context);
+}
+
+/// Generates a text format as a string.
+pub fn generate<G: Generator>(generator: &G, value: &Value) -> String {
+    let mut buf = Vec::new();
+    generator.generate(&mut buf, value).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+pub fn map(entries: Vec<(&str, Value)>) -> Value {
+    Value::Map(
+        entries
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect(),
+    )
+}
+
+pub fn string(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+/// Date-times of each kind, where the last one is a local time.
+pub fn datetimes() -> Vec<Value> {
+    let toml = "a = 1979-05-27T07:32:00.999-08:00\n\
+                b = 1979-05-27T00:32:00Z\n\
+                c = 1979-05-27T07:32:00\n\
+                d = 1979-05-27\n\
+                e = 07:32:00.000001";
+    match TomlParser.parse(toml).unwrap() {
+        Value::Map(m) => m.into_iter().map(|(_, v)| v).collect(),
+        _ => unreachable!(),
+    }
+}
+
+/// A small xorshift generator, to make random values reproducibly.
+pub struct Rng {
+    state: u64,
+    /// Strings picked as they are, which are hard to write in the format.
+    strings: &'static [&'static str],
+    /// Characters random strings are made of.
+    chars: &'static [char],
+}
+
+impl Rng {
+    pub fn new(strings: &'static [&'static str], chars: &'static [char]) -> Self {
+        Self {
+            state: 0x2545_f491_4f6c_dd1d,
+            strings,
+            chars,
+        }
+    }
+
+    pub fn next(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    pub fn string(&mut self) -> String {
+        if self.below(3) == 0 {
+            return self.strings[self.below(self.strings.len())].to_string();
+        }
+        (0..self.below(8))
+            .map(|_| self.chars[self.below(self.chars.len())])
+            .collect()
+    }
+
+    /// Makes a value nested up to `depth`, picking date-times from `datetimes`.
+    pub fn value(&mut self, depth: usize, datetimes: &[Value]) -> Value {
+        let kinds = if depth == 0 { 7 } else { 9 };
+        match self.below(kinds) {
+            0 => Value::Null,
+            1 => Value::Boolean(self.below(2) == 0),
+            2 => Value::Int(self.next() as i64 as i128 * self.next() as i128),
+            3 => Value::Float(f64::from_bits(self.next())),
+            4 => Value::Float(self.next() as i64 as f64 / 1000.0),
+            5 => Value::String(self.string()),
+            6 => datetimes[self.below(datetimes.len())].clone(),
+            7 => Value::Array(
+                (0..self.below(4))
+                    .map(|_| self.value(depth - 1, datetimes))
+                    .collect(),
+            ),
+            _ => {
+                let mut map = Map::new();
+                for _ in 0..self.below(4) {
+                    map.insert(self.string(), self.value(depth - 1, datetimes));
+                }
+                Value::Map(map)
+            }
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! Round trip tests of INI, properties and dotenv.
+mod common;
+
+use common::{assert_same, generate, map, string};
+use df_utils::generator::{DotenvGenerator, IniGenerator, PropertiesGenerator};
+use df_utils::parser::{DotenvParser, IniParser, PropertiesParser};
+use df_utils::{Generator, Map, Parser, Value};
+
+fn assert_round_trip<G: Generator, P: Parser>(generator: &G, parser: &P, value: &Value) {
+    let text = generate(generator, value);
+    let parsed = parser
+        .parse(&text)
+        .unwrap_or_else(|e| panic!("{}\n{}", e, text));
+    assert_same(&parsed, value, &text);
+}
+
+/// Strings which could be misread as the syntax.
+const STRINGS: &[&str] = &[
+    "",
+    "plain",
+    " ",
+    " padded ",
+    "a b",
+    "=",
+    "a=b",
+    ":",
+    "#",
+    "!",
+    ";",
+    "[",
+    "]",
+    "[a]",
+    "\"",
+    "'",
+    "\\",
+    "\\n",
+    "$HOME",
+    "`a`",
+    "a #b",
+    "a.b",
+    "0",
+    "line\nbreak",
+    "\r\n",
+    "\t",
+    "\0",
+    "\u{7f}",
+    "\u{a0}",
+    "\u{c}",
+    "é",
+    "日本語",
+    "😀",
+];
+
+#[test]
+fn ini() {
+    let section = map(STRINGS.iter().map(|s| (*s, string(s))).collect());
+    let mut root = STRINGS
+        .iter()
+        .map(|s| (format!("root {}", s), string(s)))
+        .collect::<Map>();
+    root.extend(STRINGS.iter().map(|s| (s.to_string(), section.clone())));
+    assert_round_trip(&IniGenerator, &IniParser, &Value::Map(root));
+}
+
+#[test]
+fn ini_layout() {
+    let value = map(vec![
+        (
+            "server",
+            map(vec![
+                ("host", string("example.com")),
+                ("port", Value::Int(80)),
+            ]),
+        ),
+        ("name", string("app")),
+        ("empty", Value::Map(Map::new())),
+    ]);
+    assert_eq!(
+        generate(&IniGenerator, &value),
+        "name = app\n\n[server]\nhost = example.com\nport = 80\n\n[empty]\n"
+    );
+    let nested = map(vec![("a", map(vec![("b", map(vec![]))]))]);
+    assert!(IniGenerator.generate(&mut Vec::new(), &nested).is_err());
+}
+
+#[test]
+fn properties() {
+    let flat = Value::Map(STRINGS.iter().map(|s| (s.to_string(), string(s))).collect());
+    assert_round_trip(&PropertiesGenerator::new(), &PropertiesParser::new(), &flat);
+
+    let mut generator = PropertiesGenerator::new();
+    generator.expand_keys(true);
+    let mut parser = PropertiesParser::new();
+    parser.expand_keys(true);
+    assert_round_trip(&generator, &parser, &flat);
+    let nested = map(vec![
+        (
+            "server",
+            map(vec![(
+                "hosts",
+                Value::Array(vec![string("a"), string("b")]),
+            )]),
+        ),
+        ("flat", flat.clone()),
+        ("list", Value::Array(vec![flat])),
+    ]);
+    assert_round_trip(&generator, &parser, &nested);
+}
+
+#[test]
+fn properties_syntax() {
+    let text = "# comment\n\
+                ! comment\n\
+                \x20 a.b = 1\n\
+                a.c:2\n\
+                d \\\n    e\n\
+                f\\ g\\=h\\u00e9\\uD83D\\uDE00\n\
+                i\n";
+    let mut parser = PropertiesParser::new();
+    let expected = map(vec![
+        ("a", map(vec![("b", string("1")), ("c", string("2"))])),
+        ("d", string("e")),
+        ("f g=hé😀", string("")),
+        ("i", string("")),
+    ]);
+    let parsed = parser.expand_keys(true).parse(text).unwrap();
+    assert_same(&parsed, &expected, text);
+
+    for text in &["a=1\na=2\n", "a=1\na.b=2\n", "a.1=1\n"] {
+        assert!(parser.parse(text).is_err(), "{:?}", text);
+        assert!(parser.lenient(true).parse(text).is_ok(), "{:?}", text);
+        parser.lenient(false);
+    }
+    assert!(parser.parse("a=\\u12\n").is_err());
+}
+
+#[test]
+fn dotenv() {
+    let value = Value::Map(
+        STRINGS
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (format!("KEY_{}", i), string(s)))
+            .collect(),
+    );
+    assert_round_trip(&DotenvGenerator::new(), &DotenvParser, &value);
+    assert_round_trip(DotenvGenerator::new().export(true), &DotenvParser, &value);
+}
+
+#[test]
+fn dotenv_syntax() {
+    let text = "# comment\n\
+                export A=1\n\
+                B = spaced value # comment\n\
+                C=a#b\n\
+                D='single\n#line'\n\
+                E=\"double \\\"\\n\\$x\" # comment\r\n\
+                F=\n";
+    let expected = map(vec![
+        ("A", string("1")),
+        ("B", string("spaced value")),
+        ("C", string("a#b")),
+        ("D", string("single\n#line")),
+        ("E", string("double \"\n$x")),
+        ("F", string("")),
+    ]);
+    let parsed = DotenvParser.parse(text).unwrap();
+    assert_same(&parsed, &expected, text);
+
+    for text in &["A\n", "1A=1\n", "A='1\n", "A=\"1\" x\n"] {
+        assert!(DotenvParser.parse(text).is_err(), "{:?}", text);
+    }
+}
@@ -0,0 +1,181 @@
+//! Round trip tests between `CsvGenerator` and `CsvParser`.
+mod common;
+
+use common::{assert_same, generate, map, string};
+use df_utils::generator::CsvGenerator;
+use df_utils::parser::CsvParser;
+use df_utils::{Generator, Map, Parser, Value};
+use std::io;
+
+/// Strings which need quoting in some way.
+const STRINGS: &[&str] = &[
+    "",
+    "plain",
+    " spaces ",
+    ",",
+    "\t",
+    "\"",
+    "\"quoted\"",
+    "a\"b",
+    "line\nbreak",
+    "carriage\r\nreturn",
+    "\r",
+    "\n",
+    "日本語",
+];
+
+#[test]
+fn strings() {
+    for (delimiter, crlf) in &[(',', true), (',', false), ('\t', true), (';', false)] {
+        let mut generator = CsvGenerator::new();
+        generator.delimiter(*delimiter).crlf(*crlf);
+        let mut parser = CsvParser::new();
+        parser.delimiter(*delimiter);
+
+        let records = STRINGS
+            .iter()
+            .map(|s| map(vec![("k", string(s)), ("v", string(s))]))
+            .chain(
+                STRINGS
+                    .iter()
+                    .map(|s| map(vec![("k", string(s)), ("v", string(""))])),
+            )
+            .collect::<Vec<_>>();
+        let value = Value::Array(records);
+        let text = generate(&generator, &value);
+        assert_same(&parser.parse(&text).unwrap(), &value, &text);
+
+        // A single empty column must not be a blank line.
+        let value = Value::Array(vec![map(vec![("k", string(""))])]);
+        let text = generate(&generator, &value);
+        assert_same(&parser.parse(&text).unwrap(), &value, &text);
+    }
+}
+
+#[test]
+fn flattened() {
+    let value = Value::Array(vec![
+        map(vec![
+            (
+                "user",
+                map(vec![("name", string("ann")), ("first.name", string("a"))]),
+            ),
+            ("tags", Value::Array(vec![Value::Int(1), Value::Float(2.0)])),
+            ("empty", Value::Map(Map::new())),
+        ]),
+        map(vec![
+            ("score", Value::Null),
+            ("tags", Value::Array(Vec::new())),
+            ("ok", Value::Boolean(true)),
+        ]),
+    ]);
+    assert_eq!(
+        generate(&CsvGenerator::new(), &value),
+        "user.name,\"user.\"\"first.name\"\"\",tags.0,tags.1,empty,score,tags,ok\r\n\
+         ann,a,1,2.0,{},,,\r\n\
+         ,,,,,,[],true\r\n"
+    );
+}
+
+#[test]
+fn keys() {
+    // Keys are column names as they are, even if they look like paths.
+    let mut parser = CsvParser::new();
+    parser.infer_types(true);
+    let value = Value::Array(vec![
+        map(vec![
+            ("2021", Value::Int(1)),
+            ("first.name", string("a")),
+            ("", Value::Int(2)),
+            ("x y", Value::Int(3)),
+            ("a,\"b\"", Value::Int(4)),
+            ("[0]", Value::Int(5)),
+        ]),
+        map(vec![("2021", Value::Int(6))]),
+    ]);
+    let text = generate(&CsvGenerator::new(), &value);
+    assert!(
+        text.starts_with("2021,first.name,,x y,\"a,\"\"b\"\"\",[0]\r\n"),
+        "{}",
+        text
+    );
+    let expected = Value::Array(vec![
+        map(vec![
+            ("2021", Value::Int(1)),
+            ("first.name", string("a")),
+            ("", Value::Int(2)),
+            ("x y", Value::Int(3)),
+            ("a,\"b\"", Value::Int(4)),
+            ("[0]", Value::Int(5)),
+        ]),
+        map(vec![
+            ("2021", Value::Int(6)),
+            ("first.name", Value::Null),
+            ("", Value::Null),
+            ("x y", Value::Null),
+            ("a,\"b\"", Value::Null),
+            ("[0]", Value::Null),
+        ]),
+    ]);
+    assert_same(&parser.parse(&text).unwrap(), &expected, &text);
+
+    for key in STRINGS {
+        let value = Value::Array(vec![map(vec![(key, Value::Int(1))])]);
+        let text = generate(&CsvGenerator::new(), &value);
+        assert_same(&parser.parse(&text).unwrap(), &value, &text);
+    }
+
+    // Paths of nested values must not collide with keys.
+    let value = Value::Array(vec![map(vec![
+        ("a.b", Value::Int(1)),
+        ("a", map(vec![("b", Value::Int(2))])),
+    ])]);
+    let e = CsvGenerator::new()
+        .generate(&mut Vec::new(), &value)
+        .unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn infer_types() {
+    let text = "a,b,c,d,e,f\n1,-2.5e3,TRUE,,007,\"\"\n";
+    let mut parser = CsvParser::new();
+    assert_same(
+        &parser.infer_types(true).parse(text).unwrap(),
+        &Value::Array(vec![map(vec![
+            ("a", Value::Int(1)),
+            ("b", Value::Float(-2500.0)),
+            ("c", Value::Boolean(true)),
+            ("d", Value::Null),
+            ("e", string("007")),
+            ("f", Value::Null),
+        ])]),
+        text,
+    );
+}
+
+#[test]
+fn bom() {
+    let text = "\u{feff}a,b\r\n1,2\r\n";
+    let expected = Value::Array(vec![map(vec![("a", string("1")), ("b", string("2"))])]);
+    assert_same(&CsvParser::new().parse(text).unwrap(), &expected, text);
+    // Only the head of the input.
+    let text = "a\n\u{feff}\n";
+    let expected = Value::Array(vec![map(vec![("a", string("\u{feff}"))])]);
+    assert_same(&CsvParser::new().parse(text).unwrap(), &expected, text);
+}
+
+#[test]
+fn errors() {
+    for (text, offset) in &[
+        ("a,b\n1\n", 5),
+        ("a,b\n1,2,3\n", 7),
+        ("a,a\n", 2),
+        ("a\n\"x\"y\n", 5),
+        ("a\n\"x\n", 5),
+        ("a\nx\ry\n", 3),
+    ] {
+        let e = CsvParser::new().parse(text).unwrap_err();
+        assert_eq!(e.offset(), *offset, "{:?}", text);
+    }
+}
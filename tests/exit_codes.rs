@@ -0,0 +1,84 @@
+//! Tests of the exit codes of the binaries, running them on inputs.
+#![cfg(feature = "bin")]
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Runs the binary with the input as the standard input, and returns the exit
+/// code.
+fn run(bin: &str, args: &[&str], input: &[u8]) -> i32 {
+    let mut child = Command::new(bin)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    // The binary may exit without reading the input.
+    let _ = child.stdin.take().unwrap().write_all(input);
+    child.wait().unwrap().code().unwrap()
+}
+
+const JPRS: &str = env!("CARGO_BIN_EXE_jprs");
+const JFMT: &str = env!("CARGO_BIN_EXE_jfmt");
+const JGEN: &str = env!("CARGO_BIN_EXE_jgen");
+const TGEN: &str = env!("CARGO_BIN_EXE_tgen");
+const DFCONV: &str = env!("CARGO_BIN_EXE_dfconv");
+
+#[test]
+fn success() {
+    assert_eq!(run(JFMT, &[], b"{\"a\": [1, 2]}"), 0);
+    assert_eq!(run(JFMT, &["-M"], b"1 2"), 0);
+    assert_eq!(run(TGEN, &[], b"$.a = 1"), 0);
+    assert_eq!(run(DFCONV, &["-f", "json", "-t", "msgpack"], b"[1]"), 0);
+    assert_eq!(
+        run(DFCONV, &["-f", "msgpack", "-t", "json"], b"\xd4\x01\x2a"),
+        0
+    );
+}
+
+#[test]
+fn usage() {
+    assert_eq!(run(JFMT, &["--unknown"], b""), 2);
+    assert_eq!(run(DFCONV, &["-t", "json"], b"{}"), 2);
+}
+
+#[test]
+fn io() {
+    let missing = "/nonexistent/input.json";
+    assert_eq!(run(JFMT, &[missing], b""), 3);
+    assert_eq!(run(DFCONV, &["-t", "json", missing], b""), 3);
+    let unwritable = "/nonexistent/output.json";
+    assert_eq!(run(JFMT, &["-o", unwritable], b"{}"), 3);
+    assert_eq!(run(JGEN, &["-o", unwritable], b"$ = 1"), 3);
+}
+
+#[test]
+fn parse() {
+    assert_eq!(run(JFMT, &[], b"{"), 4);
+    // Invalid UTF-8 is an error of the input, not of generating.
+    assert_eq!(run(JFMT, &[], b"\xff"), 4);
+    assert_eq!(run(JFMT, &["-M"], b"1 \xff"), 4);
+    assert_eq!(run(JPRS, &["--ndjson"], b"1\n\xff\n"), 4);
+    assert_eq!(run(JFMT, &["--ndjson"], b"1\n\xff\n"), 4);
+    assert_eq!(run(TGEN, &[], b"$.a = \"\xc3\""), 4);
+    assert_eq!(run(DFCONV, &["-f", "toml", "-t", "json"], b"a = \xff"), 4);
+    assert_eq!(run(DFCONV, &["-f", "msgpack", "-t", "json"], b"\xc1"), 4);
+}
+
+#[test]
+fn generate() {
+    assert_eq!(run(TGEN, &[], b"$.a = null"), 5);
+    assert_eq!(run(JGEN, &["-n"], b"$ = 1"), 5);
+    assert_eq!(run(JFMT, &["-c"], b"[1e999]"), 5);
+    assert_eq!(run(JFMT, &["-c", "-M"], b"1 [1e999]"), 5);
+    assert_eq!(run(DFCONV, &["-f", "json", "-t", "toml"], b"[1]"), 5);
+    // Output isn't created on errors of generating.
+    assert_eq!(
+        run(
+            DFCONV,
+            &["-f", "json", "-t", "toml", "-o", "/nonexistent/a"],
+            b"[1]"
+        ),
+        5
+    );
+}
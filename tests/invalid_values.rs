@@ -0,0 +1,57 @@
+//! Tests that generators write nothing for values they can't represent.
+mod common;
+
+use common::{map, string};
+use df_utils::generator::{
+    CanonicalJsonGenerator, CsvGenerator, DotenvGenerator, IniGenerator, MsgpackGenerator,
+    NdjsonGenerator, PropertiesGenerator, TomlGenerator, XmlGenerator,
+};
+use df_utils::{Generator, Value};
+use std::io;
+
+fn assert_invalid<G: Generator>(generator: &G, value: &Value) {
+    let mut buf = Vec::new();
+    let e = generator.generate(&mut buf, value).unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::InvalidData, "{:?}", value);
+    assert!(buf.is_empty(), "{:?}\n{:?}", value, buf);
+}
+
+#[test]
+fn nothing_written() {
+    // Valid parts come before invalid ones.
+    let nested = map(vec![
+        ("a", string("b")),
+        ("c", map(vec![("d", map(vec![("e", Value::Null)]))])),
+    ]);
+    assert_invalid(
+        &CanonicalJsonGenerator,
+        &Value::Array(vec![Value::Int(1), Value::Float(f64::NAN)]),
+    );
+    assert_invalid(
+        &CsvGenerator::new(),
+        &Value::Array(vec![map(vec![]), Value::Int(1)]),
+    );
+    assert_invalid(&CsvGenerator::new(), &map(vec![]));
+    assert_invalid(
+        &DotenvGenerator::new(),
+        &map(vec![("A", Value::Int(1)), ("b c", Value::Int(2))]),
+    );
+    assert_invalid(&IniGenerator, &nested);
+    assert_invalid(
+        &MsgpackGenerator,
+        &Value::Array(vec![Value::Int(1), Value::Int(1 << 64)]),
+    );
+    assert_invalid(&NdjsonGenerator::new(), &map(vec![]));
+    assert_invalid(&PropertiesGenerator::new(), &Value::Int(1));
+    assert_invalid(
+        &TomlGenerator,
+        &map(vec![("a", Value::Int(1)), ("b", Value::Int(1 << 64))]),
+    );
+    assert_invalid(
+        &XmlGenerator::new(),
+        &map(vec![(
+            "a",
+            map(vec![("b", string("c")), ("1", Value::Null)]),
+        )]),
+    );
+}
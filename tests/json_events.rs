@@ -0,0 +1,141 @@
+//! Tests of the events of `JsonParser::events` and `Json5Parser::events`.
+mod common;
+
+use common::{assert_same, string};
+use df_utils::parser::{CombinedError, Event, Json5Parser, JsonParser};
+use df_utils::Value;
+use std::io::{self, Read};
+
+/// Describes events compactly, like `[ a: 1 ]`.
+fn describe(events: &[Event]) -> String {
+    events
+        .iter()
+        .map(|event| match event {
+            Event::StartArray => "[".to_string(),
+            Event::EndArray => "]".to_string(),
+            Event::StartMap => "{".to_string(),
+            Event::EndMap => "}".to_string(),
+            Event::Key(k) => format!("{}:", k),
+            Event::Value(Value::String(s)) => format!("{:?}", s),
+            Event::Value(v) => format!("{:?}", v),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn events(text: &str) -> String {
+    let events = JsonParser
+        .events(text.as_bytes())
+        .collect::<Result<Vec<_>, _>>();
+    describe(&events.unwrap_or_else(|e| panic!("{}\n{}", e, text)))
+}
+
+/// The input of `text`, failing after it has been read.
+struct Failing<'a>(&'a [u8]);
+
+impl Read for Failing<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.0.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::Other, "failing"));
+        }
+        self.0.read(buf)
+    }
+}
+
+#[test]
+fn sequence() {
+    for (text, expected) in &[
+        ("1", "Int(1)"),
+        ("\"a\"", "\"a\""),
+        ("[]", "[ ]"),
+        ("{}", "{ }"),
+        (
+            "[null, true, 1.5, [\"a\"]]",
+            "[ Null Boolean(true) Float(1.5) [ \"a\" ] ]",
+        ),
+        (
+            "{\"a\": {\"b\": []}, \"c\": [{}]}",
+            "{ a: { b: [ ] } c: [ { } ] }",
+        ),
+        // Duplicate keys are reported as they are.
+        ("{\"a\": 1, \"a\": 2}", "{ a: Int(1) a: Int(2) }"),
+    ] {
+        assert_eq!(events(text), *expected, "{:?}", text);
+    }
+
+    let events = Json5Parser
+        .events("{a: [+1, 0x10, 'b',], // c\n}".as_bytes())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(describe(&events), "{ a: [ Int(1) Int(16) \"b\" ] }");
+    match &events[5] {
+        Event::Value(v) => assert_same(v, &string("b"), "b"),
+        event => panic!("{:?}", event),
+    }
+}
+
+#[test]
+fn errors() {
+    // Events before the error are reported, and the iteration ends with it.
+    let mut events = JsonParser.events("[1, 2 x, 3]".as_bytes());
+    assert_eq!(
+        describe(&[
+            events.next().unwrap().unwrap(),
+            events.next().unwrap().unwrap(),
+            events.next().unwrap().unwrap()
+        ]),
+        "[ Int(1) Int(2)"
+    );
+    match events.next() {
+        Some(Err(CombinedError::Parse(e))) => {
+            assert_eq!((e.line(), e.column(), e.offset()), (1, 7, 6))
+        }
+        event => panic!("{:?}", event),
+    }
+    assert!(events.next().is_none());
+    assert!(events.next().is_none());
+
+    // The input after the document is checked at the end.
+    let mut events = JsonParser.events("[] x".as_bytes());
+    assert!(matches!(events.next(), Some(Ok(Event::StartArray))));
+    assert!(matches!(events.next(), Some(Ok(Event::EndArray))));
+    assert!(matches!(events.next(), Some(Err(CombinedError::Parse(_)))));
+    assert!(events.next().is_none());
+
+    for text in &["", "[", "{\"a\"}", "[1,]", "1 2"] {
+        let result = JsonParser
+            .events(text.as_bytes())
+            .collect::<Result<Vec<_>, _>>();
+        assert!(matches!(result, Err(CombinedError::Parse(_))), "{:?}", text);
+    }
+}
+
+#[test]
+fn incremental() {
+    // Only what is needed for the events is read.
+    let mut events = JsonParser.events(Failing(b"[1, {\"a\": 2}"));
+    let read = events
+        .by_ref()
+        .take(6)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(describe(&read), "[ Int(1) { a: Int(2) }");
+    match events.next() {
+        Some(Err(CombinedError::Io(e))) => assert_eq!(e.to_string(), "failing"),
+        event => panic!("{:?}", event),
+    }
+    assert!(events.next().is_none());
+
+    // Long documents are read without keeping them.
+    let n = 100_000;
+    let text = format!("[{}1]", "1, ".repeat(n));
+    assert_eq!(JsonParser.events(text.as_bytes()).count(), n + 3);
+    let text = format!("{}{}", "[".repeat(n), "]".repeat(n));
+    assert_eq!(
+        JsonParser
+            .events(text.as_bytes())
+            .filter(|e| e.is_ok())
+            .count(),
+        2 * n
+    );
+}
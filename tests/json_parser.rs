@@ -0,0 +1,38 @@
+//! Tests of `JsonParser` and `Json5Parser` parsing texts written by hand.
+mod common;
+
+use common::{assert_same, string};
+use df_utils::parser::{Json5Parser, JsonParser};
+use df_utils::Parser;
+
+#[test]
+fn surrogates() {
+    for (text, expected) in &[
+        (r#""\ud83d\ude00""#, "\u{1f600}"),
+        (r#""\uD83D\uDE00""#, "\u{1f600}"),
+        (r#""a\ud800\udc00b""#, "a\u{10000}b"),
+        (r#""\udbff\udfff""#, "\u{10ffff}"),
+        (r#""\ud7ff\ue000""#, "\u{d7ff}\u{e000}"),
+    ] {
+        assert_same(&JsonParser.parse(text).unwrap(), &string(expected), text);
+        assert_same(&Json5Parser.parse(text).unwrap(), &string(expected), text);
+    }
+    assert_same(
+        &Json5Parser.parse(r"'\ud83d\ude00'").unwrap(),
+        &string("\u{1f600}"),
+        "",
+    );
+
+    // Lone and reversed surrogates, which JSON5 doesn't read as `u` either
+    for text in &[
+        r#""\ud800""#,
+        r#""\ud800a""#,
+        r#""\ud800A""#,
+        r#""\ud800\ud800""#,
+        r#""\udc00""#,
+        r#""\ude00\ud83d""#,
+    ] {
+        assert!(JsonParser.parse(text).is_err(), "{}", text);
+        assert!(Json5Parser.parse(text).is_err(), "{}", text);
+    }
+}
@@ -0,0 +1,170 @@
+//! Tests comparing the streaming JSON(JSON5) parser with `parse`.
+mod common;
+
+use common::{assert_same, generate};
+use df_utils::generator::LineGenerator;
+use df_utils::parser::{CombinedError, Json5Parser, JsonParser};
+use df_utils::Parser;
+
+/// Writes lines by streaming, or returns the error.
+fn stream(text: &str, json5: bool) -> Result<String, CombinedError> {
+    let mut buf = Vec::new();
+    LineGenerator::new().generate_stream(&mut buf, text.as_bytes(), json5)?;
+    Ok(String::from_utf8(buf).unwrap())
+}
+
+/// Asserts that streaming writes the same lines as parsing the whole input,
+/// or that both fail.
+fn assert_agrees(text: &str, json5: bool) {
+    let parsed = if json5 {
+        Json5Parser.parse(text)
+    } else {
+        JsonParser.parse(text)
+    };
+    match (stream(text, json5), parsed) {
+        (Ok(lines), Ok(value)) => {
+            assert_eq!(lines, generate(&LineGenerator::new(), &value), "{:?}", text)
+        }
+        (Err(_), Err(_)) => {}
+        (lines, value) => panic!("{:?}\n{:?}\n{:?}", text, lines, value),
+    }
+}
+
+const VALID: &[&str] = &[
+    "null",
+    " true ",
+    "\"a\\\"\\\\\\/\\b\\f\\n\\r\\t\\u00e9\\ud83d\\ude00\"",
+    "[]",
+    "{}",
+    "[1, -2, 0, -0, 1.5, -0.25, 1e5, 1E+5, 2e-3, 1e0, 0e0, 1.0e-0]",
+    "123456789012345678901234567890",
+    "{\"a\": {\"b\": [[], {}, [null]]}, \"c\": \"d\", \"\": 1}",
+    "\n\t[ 1 ,\r\n 2 ]\n",
+];
+
+const INVALID: &[&str] = &[
+    "",
+    "[1,]",
+    "{\"a\": 1,}",
+    "[1 2]",
+    "01",
+    "-",
+    "1.",
+    ".5",
+    "+1",
+    "1e",
+    "1e+",
+    "1e05",
+    "1e-01",
+    "1E00",
+    "0x10",
+    "NaN",
+    "Infinity",
+    "1 2",
+    "\"\\x41\"",
+    "\"\\ud800\"",
+    "\"\\ud800\\u0041\"",
+    "{a: 1}",
+    "{\"a\" 1}",
+    "tru",
+    "[",
+    "{\"a\":",
+    "// comment\n1",
+    "1000000000000000000000000000000000000000",
+];
+
+#[test]
+fn json() {
+    for text in VALID {
+        assert!(stream(text, false).is_ok(), "{:?}", text);
+        assert_agrees(text, false);
+    }
+    for text in INVALID {
+        assert!(stream(text, false).is_err(), "{:?}", text);
+        assert_agrees(text, false);
+    }
+}
+
+#[test]
+fn json5() {
+    for text in VALID {
+        assert_agrees(text, true);
+    }
+    for text in &[
+        "[1,]",
+        "{\"a\": 1,}",
+        ".5",
+        "+1",
+        "-.5e1",
+        "0x1F",
+        "-0x10",
+        "NaN",
+        "-Infinity",
+        "'a\\'b\\x41\\v\\\nc'",
+        "{a: 1, $_b: 2, \\u0061b: 3, 'c': 4}",
+        "// comment\n[1, /* two */ 2]\u{2028}",
+        "\u{feff}1",
+    ] {
+        assert!(stream(text, true).is_ok(), "{:?}", text);
+        assert_agrees(text, true);
+    }
+    for text in &["1.", "1e05", "0X1", "01", "'a\nb'", "/* unclosed", "{1: 2}"] {
+        assert!(stream(text, true).is_err(), "{:?}", text);
+        assert_agrees(text, true);
+    }
+}
+
+#[test]
+fn duplicate_keys() {
+    // Parsing keeps the last value, while streaming writes every value as the
+    // earlier ones have already been written.
+    let text = "{\"a\": 1,\n \"b\": {\"a\": 2},\n \"a\": 3}";
+    assert_eq!(
+        stream(text, false).unwrap(),
+        "$.a = 1\n$.b.a = 2\n$.a = 3\n"
+    );
+    assert_eq!(
+        generate(&LineGenerator::new(), &JsonParser.parse(text).unwrap()),
+        "$.a = 3\n$.b.a = 2\n"
+    );
+    assert_eq!(
+        stream("{a: [1], 'a': {}}", true).unwrap(),
+        "$.a.0 = 1\n$.a = {}\n"
+    );
+
+    // Documents are built like `parse` does.
+    let document = JsonParser
+        .documents(text.as_bytes())
+        .next()
+        .unwrap()
+        .unwrap();
+    assert_same(&document, &JsonParser.parse(text).unwrap(), text);
+}
+
+#[test]
+fn snippets() {
+    let text = "[1,\n 2 x,\n 3]";
+    let e = match stream(text, false) {
+        Err(CombinedError::Parse(e)) => e,
+        result => panic!("{:?}", result),
+    };
+    assert_eq!((e.line(), e.column(), e.snippet()), (2, 4, Some(" 2 x,")));
+    // The same line as rendering with the whole input
+    let line = "2 |  2 x,\n  |    ^ expected";
+    assert!(e.render("", "a.json", false).contains(line));
+    let e = JsonParser.parse(text).unwrap_err();
+    assert!(e.render(text, "a.json", false).contains(line));
+
+    let e = match stream("[\"\u{e9}\" x]", false) {
+        Err(CombinedError::Parse(e)) => e,
+        result => panic!("{:?}", result),
+    };
+    assert_eq!((e.column(), e.snippet()), (6, Some("[\"\u{e9}\" x]")));
+
+    // Too long lines aren't kept.
+    let long = format!("[{}x]", "1, ".repeat(1000));
+    match stream(&long, false) {
+        Err(CombinedError::Parse(e)) => assert_eq!((e.column(), e.snippet()), (3002, None)),
+        result => panic!("{:?}", result),
+    }
+}
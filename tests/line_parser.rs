@@ -0,0 +1,148 @@
+//! Tests of how `LineParser` builds the tree from lines.
+mod common;
+
+use common::{assert_same, map, string};
+use df_utils::parser::{LineParser, PropertiesParser};
+use df_utils::{Parser, Value};
+
+fn parse(parser: &LineParser, text: &str) -> Value {
+    parser
+        .parse(text)
+        .unwrap_or_else(|e| panic!("{}\n{}", e, text))
+}
+
+#[test]
+fn tree() {
+    let text = "$.a.0 = 1\n\
+                $.a.1.b = \"x\"\n\
+                $.a.1.c = null\n\
+                $.a.2 = []\n\
+                $.d = {}\n\
+                $.\"0\" = true";
+    let expected = map(vec![
+        (
+            "a",
+            Value::Array(vec![
+                Value::Int(1),
+                map(vec![("b", string("x")), ("c", Value::Null)]),
+                Value::Array(Vec::new()),
+            ]),
+        ),
+        ("d", map(vec![])),
+        ("0", Value::Boolean(true)),
+    ]);
+    assert_same(&parse(&LineParser::new(), text), &expected, text);
+    assert_same(&parse(&LineParser::new(), ""), &Value::Null, "");
+    assert_same(&parse(&LineParser::new(), "$ = 1\n"), &Value::Int(1), "");
+}
+
+#[test]
+fn conflicts() {
+    // The location of the error, and of the earlier line it conflicts with.
+    for (text, expected, at, previous) in &[
+        // Duplicate paths
+        ("$.a = 1\n$.a = 2", "unique path", (2, 1), Some((1, 1))),
+        ("$ = 1\n$ = 1", "unique path", (2, 1), Some((1, 1))),
+        (
+            "$.a.b = 1\n$.c = 2\n$.a = {}",
+            "unique path",
+            (3, 1),
+            Some((1, 1)),
+        ),
+        ("$.a.0 = 1\n$.a.0 = 1", "unique path", (2, 1), Some((1, 1))),
+        // Children of scalars
+        (
+            "$.a = 1\n$.b = 2\n$.a.b = 2",
+            "unique path",
+            (3, 4),
+            Some((1, 1)),
+        ),
+        ("$ = null\n$.a = 1", "unique path", (2, 2), Some((1, 1))),
+        // Kinds of containers
+        (
+            "$.x = 1\n$.a.0 = 1\n$.a.b = 1",
+            "array index",
+            (3, 4),
+            Some((2, 1)),
+        ),
+        ("$.a.b = 1\n$.a.0 = 1", "map key", (2, 4), Some((1, 1))),
+        ("$.0 = 1\n$.a = 1", "array index", (2, 2), Some((1, 1))),
+        // Gaps of indices
+        ("$.a.1 = 1", "consecutive index", (1, 4), None),
+        ("$.0 = 1\n$.2 = 1", "consecutive index", (2, 2), None),
+    ] {
+        let e = LineParser::new().parse(text).unwrap_err();
+        assert_eq!(e.expected(), [*expected], "{:?}", text);
+        assert_eq!((e.line(), e.column()), *at, "{:?}", text);
+        assert_eq!(e.previous(), *previous, "{:?}", text);
+    }
+
+    let e = LineParser::new().parse("$.a = 1\n$.a = 2").unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "invalid line format at 2:1: expected unique path, conflicting with 1:1"
+    );
+    assert_eq!(
+        e.render("$.a = 1\n$.a = 2", "a.txt", false),
+        "error: invalid line format\n \
+         --> a.txt:2:1\n  \
+         |\n\
+         2 | $.a = 2\n  \
+         | ^ expected unique path\n  \
+         = note: conflicting with a.txt:1:1\n"
+    );
+}
+
+#[test]
+fn lenient() {
+    let mut parser = LineParser::new();
+    parser.lenient(true);
+    for (text, expected) in &[
+        ("$.a = 1\n$.a = 2", map(vec![("a", Value::Int(2))])),
+        // Later lines replace earlier values, keeping the position.
+        (
+            "$.a = 1\n$.b = 2\n$.a.c = 3",
+            map(vec![
+                ("a", map(vec![("c", Value::Int(3))])),
+                ("b", Value::Int(2)),
+            ]),
+        ),
+        ("$.a.b = 1\n$.a = null", map(vec![("a", Value::Null)])),
+        (
+            "$.a.b = 1\n$.a.1 = true",
+            map(vec![(
+                "a",
+                Value::Array(vec![Value::Null, Value::Boolean(true)]),
+            )]),
+        ),
+        // Gaps are filled by nulls.
+        (
+            "$.2 = 1\n$.0.x = 2",
+            Value::Array(vec![
+                map(vec![("x", Value::Int(2))]),
+                Value::Null,
+                Value::Int(1),
+            ]),
+        ),
+        ("$ = 1\n$.a = 2", map(vec![("a", Value::Int(2))])),
+        ("$.a = 1\n$ = 2", Value::Int(2)),
+    ] {
+        assert_same(&parse(&parser, text), expected, text);
+    }
+}
+
+#[test]
+fn properties() {
+    let mut parser = PropertiesParser::new();
+    parser.expand_keys(true);
+    let text = "a.b = 1\nc = 2\na.b.c = 3";
+    let e = parser.parse(text).unwrap_err();
+    assert_eq!((e.line(), e.previous()), (3, Some((1, 1))));
+
+    parser.lenient(true);
+    let expected = map(vec![
+        ("a", map(vec![("b", map(vec![("c", string("3"))]))])),
+        ("c", string("2")),
+    ]);
+    assert_same(&parser.parse(text).unwrap(), &expected, text);
+}
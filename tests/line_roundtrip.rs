@@ -0,0 +1,189 @@
+//! Round trip tests between `LineGenerator` and `LineParser`.
+mod common;
+
+use common::{datetimes, map, same, Rng};
+use df_utils::generator::LineGenerator;
+use df_utils::parser::LineParser;
+use df_utils::{Generator, Map, Parser, Value};
+
+fn assert_round_trip(generator: &LineGenerator, parser: &LineParser, value: &Value) {
+    let mut buf = Vec::new();
+    generator.generate(&mut buf, value).unwrap();
+    let text = String::from_utf8(buf).unwrap();
+    let parsed = parser
+        .parse(&text)
+        .unwrap_or_else(|e| panic!("{}\n{}", e, text));
+    assert!(same(value, &parsed), "{:?}\n{}\n{:?}", value, text, parsed);
+}
+
+fn configs() -> Vec<(LineGenerator, LineParser)> {
+    let mut configs = vec![(LineGenerator::new(), LineParser::new())];
+    for (root, delimiter, equal) in [("$", "/", "="), ("", "::", ": "), ("root", " ", "\t")] {
+        let mut generator = LineGenerator::new();
+        generator.root(root).delimiter(delimiter).equal(equal);
+        let mut parser = LineParser::new();
+        parser.root(root).delimiter(delimiter).equal(equal);
+        configs.push((generator.clone(), parser.clone()));
+
+        generator.bracket_paths(true);
+        parser.bracket_paths(true);
+        configs.push((generator, parser));
+    }
+    configs
+}
+
+/// Strings and keys which are hard to be written in the line format.
+const STRINGS: &[&str] = &[
+    "",
+    "plain",
+    "0",
+    "01",
+    "123",
+    "-1",
+    "1.5",
+    "null",
+    "true",
+    "[]",
+    "{}",
+    "\"",
+    "\"quoted\"",
+    "a\"b",
+    "\\",
+    "a.b",
+    "a = b",
+    "a =",
+    "= a",
+    " ",
+    "trailing ",
+    "a/b",
+    "a:",
+    "::",
+    "a\tb",
+    "line\nbreak",
+    "\r\n",
+    "\0",
+    "\u{7f}\u{80}\u{1b}",
+    "\u{2028}",
+    "日本語",
+    "😀",
+    "'",
+    "\\u{41}",
+    "$",
+    "root",
+];
+
+#[test]
+fn scalars() {
+    let mut values = vec![
+        Value::Null,
+        Value::Boolean(true),
+        Value::Boolean(false),
+        Value::Int(0),
+        Value::Int(-1),
+        Value::Int(i128::MAX),
+        Value::Int(i128::MIN),
+        Value::Float(0.0),
+        Value::Float(-0.0),
+        Value::Float(1.0),
+        Value::Float(-1.5),
+        Value::Float(0.1),
+        Value::Float(1e300),
+        Value::Float(-1e-300),
+        Value::Float(5e-324),
+        Value::Float(f64::MAX),
+        Value::Float(f64::NAN),
+        Value::Float(f64::INFINITY),
+        Value::Float(f64::NEG_INFINITY),
+        Value::Array(Vec::new()),
+        Value::Map(Map::new()),
+    ];
+    values.extend(STRINGS.iter().map(|s| Value::String(s.to_string())));
+    values.extend(datetimes());
+
+    for (generator, parser) in configs() {
+        for value in &values {
+            assert_round_trip(&generator, &parser, value);
+            assert_round_trip(&generator, &parser, &Value::Array(vec![value.clone()]));
+            assert_round_trip(&generator, &parser, &map(vec![("k", value.clone())]));
+        }
+    }
+}
+
+#[test]
+fn keys() {
+    for (generator, parser) in configs() {
+        for key in STRINGS {
+            let value = map(vec![(key, Value::Int(1))]);
+            assert_round_trip(&generator, &parser, &value);
+
+            let nested = map(vec![(key, map(vec![(key, Value::Int(1))]))]);
+            assert_round_trip(&generator, &parser, &nested);
+        }
+
+        let all = map(STRINGS
+            .iter()
+            .map(|k| (*k, Value::String(k.to_string())))
+            .collect());
+        assert_round_trip(&generator, &parser, &all);
+    }
+}
+
+#[test]
+fn bracket_paths() {
+    let mut generator = LineGenerator::new();
+    generator.bracket_paths(true);
+    let value = map(vec![(
+        "users",
+        Value::Array(vec![map(vec![
+            ("first.name", Value::String("A".to_string())),
+            ("age", Value::Int(20)),
+        ])]),
+    )]);
+    let mut buf = Vec::new();
+    generator.generate(&mut buf, &value).unwrap();
+    assert_eq!(
+        String::from_utf8(buf).unwrap(),
+        "$.users[0][\"first.name\"] = \"A\"\n$.users[0].age = 20\n"
+    );
+
+    // Plain paths are also accepted.
+    let mut parser = LineParser::new();
+    parser.bracket_paths(true);
+    let parsed = parser
+        .parse("$.users.0[\"first.name\"] = \"A\"\n$.users[0].age = 20\n")
+        .unwrap();
+    assert!(same(&value, &parsed), "{:?}", parsed);
+}
+
+#[test]
+fn nested_empties() {
+    let value = map(vec![
+        (
+            "a",
+            Value::Array(vec![Value::Array(Vec::new()), Value::Map(Map::new())]),
+        ),
+        ("b", map(vec![("c", Value::Map(Map::new()))])),
+        ("d", Value::Array(vec![Value::Null, Value::Null])),
+    ]);
+    for (generator, parser) in configs() {
+        assert_round_trip(&generator, &parser, &value);
+    }
+}
+
+/// Characters of random strings.
+const CHARS: &[char] = &[
+    'a', 'Z', '0', '9', '.', '=', ' ', '"', '\\', '/', ':', '$', '\n', '\r', '\t', '\0', '\u{1}',
+    '\u{7f}', 'é', '😀',
+];
+
+#[test]
+fn random_values() {
+    let datetimes = datetimes();
+    let mut rng = Rng::new(STRINGS, CHARS);
+    for (generator, parser) in configs() {
+        for _ in 0..2000 {
+            let value = rng.value(4, &datetimes);
+            assert_round_trip(&generator, &parser, &value);
+        }
+    }
+}
@@ -0,0 +1,252 @@
+//! Round trip tests between `MsgpackGenerator` and `MsgpackParser`.
+mod common;
+
+use common::{assert_same, map, string};
+use df_utils::generator::MsgpackGenerator;
+use df_utils::parser::{JsonParser, MsgpackParser, TomlParser, YamlParser};
+use df_utils::{Generator, Map, Parser, Value};
+
+fn encode(value: &Value) -> Vec<u8> {
+    let mut buf = Vec::new();
+    MsgpackGenerator.generate(&mut buf, value).unwrap();
+    buf
+}
+
+fn assert_decodes(bytes: &[u8], value: &Value) {
+    let parsed = MsgpackParser
+        .parse_bytes(bytes)
+        .unwrap_or_else(|e| panic!("{}\n{:02x?}", e, bytes));
+    assert_same(&parsed, value, &format!("{:02x?}", bytes));
+}
+
+fn assert_round_trip(value: &Value) {
+    assert_decodes(&encode(value), value);
+}
+
+/// Parses a TOML value, to make date-times.
+fn toml(s: &str) -> Value {
+    match TomlParser.parse(&format!("v = {}", s)).unwrap() {
+        Value::Map(mut m) => m.swap_remove("v").unwrap(),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn ints() {
+    let cases: &[(i128, &[u8])] = &[
+        (0, &[0x00]),
+        (127, &[0x7f]),
+        (128, &[0xcc, 0x80]),
+        (255, &[0xcc, 0xff]),
+        (256, &[0xcd, 0x01, 0x00]),
+        (65535, &[0xcd, 0xff, 0xff]),
+        (65536, &[0xce, 0x00, 0x01, 0x00, 0x00]),
+        (u32::MAX.into(), &[0xce, 0xff, 0xff, 0xff, 0xff]),
+        (1 << 32, &[0xcf, 0, 0, 0, 1, 0, 0, 0, 0]),
+        (
+            u64::MAX.into(),
+            &[0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+        ),
+        (-1, &[0xff]),
+        (-32, &[0xe0]),
+        (-33, &[0xd0, 0xdf]),
+        (-128, &[0xd0, 0x80]),
+        (-129, &[0xd1, 0xff, 0x7f]),
+        (-32768, &[0xd1, 0x80, 0x00]),
+        (-32769, &[0xd2, 0xff, 0xff, 0x7f, 0xff]),
+        (i32::MIN.into(), &[0xd2, 0x80, 0x00, 0x00, 0x00]),
+        (i64::MIN.into(), &[0xd3, 0x80, 0, 0, 0, 0, 0, 0, 0]),
+    ];
+    for (i, bytes) in cases {
+        assert_eq!(encode(&Value::Int(*i)), *bytes, "{}", i);
+        assert_round_trip(&Value::Int(*i));
+    }
+    assert_decodes(&[0xd0, 0x01], &Value::Int(1));
+    assert_decodes(
+        &[0xd3, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe],
+        &Value::Int(-2),
+    );
+
+    for i in &[i128::from(u64::MAX) + 1, i128::from(i64::MIN) - 1] {
+        let result = MsgpackGenerator.generate(&mut Vec::new(), &Value::Int(*i));
+        assert!(result.is_err(), "{}", i);
+    }
+}
+
+#[test]
+fn values() {
+    for value in &[
+        Value::Null,
+        Value::Boolean(true),
+        Value::Boolean(false),
+        Value::Float(0.5),
+        Value::Float(-1e300),
+        Value::Float(f64::INFINITY),
+        Value::Float(f64::NAN),
+        string(""),
+        string(&"a".repeat(31)),
+        string(&"a".repeat(32)),
+        string(&"é".repeat(128)),
+        string(&"a".repeat(65536)),
+        string("日本語😀"),
+        Value::Array(vec![]),
+        Value::Array(vec![Value::Null; 15]),
+        Value::Array(vec![Value::Null; 16]),
+        Value::Array(vec![Value::Int(1); 65536]),
+        Value::Map(Map::new()),
+        Value::Map((0..16).map(|i| (i.to_string(), Value::Null)).collect()),
+        Value::Map((0..65536).map(|i| (i.to_string(), Value::Null)).collect()),
+        toml("{ a = [1, { b = \"c\" }], d = {} }"),
+    ] {
+        assert_round_trip(value);
+    }
+    assert_decodes(&[0xca, 0x3f, 0xc0, 0x00, 0x00], &Value::Float(1.5));
+}
+
+#[test]
+fn timestamps() {
+    let cases: &[(&str, &str, &[u8])] = &[
+        (
+            "1970-01-01T00:00:00Z",
+            "1970-01-01T00:00:00Z",
+            &[0xd6, 0xff, 0, 0, 0, 0],
+        ),
+        (
+            "1979-05-27T07:32:00-08:00",
+            "1979-05-27T15:32:00Z",
+            &[0xd6, 0xff, 0x11, 0xae, 0xc7, 0xf0],
+        ),
+        (
+            "2106-02-07T06:28:15Z",
+            "2106-02-07T06:28:15Z",
+            &[0xd6, 0xff, 0xff, 0xff, 0xff, 0xff],
+        ),
+        (
+            "2106-02-07T06:28:16+00:00",
+            "2106-02-07T06:28:16Z",
+            &[0xd7, 0xff, 0, 0, 0, 0x01, 0, 0, 0, 0],
+        ),
+        (
+            "1970-01-01T09:00:00.5+09:00",
+            "1970-01-01T00:00:00.5Z",
+            &[0xd7, 0xff, 0x77, 0x35, 0x94, 0x00, 0, 0, 0, 0],
+        ),
+        (
+            "1969-12-31T23:59:59.999999999Z",
+            "1969-12-31T23:59:59.999999999Z",
+            &[
+                0xc7, 12, 0xff, 0x3b, 0x9a, 0xc9, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                0xff,
+            ],
+        ),
+        (
+            "0001-01-01T00:00:00Z",
+            "0001-01-01T00:00:00Z",
+            &[
+                0xc7, 12, 0xff, 0, 0, 0, 0, 0xff, 0xff, 0xff, 0xf1, 0x88, 0x6e, 0x09, 0x00,
+            ],
+        ),
+    ];
+    for (text, utc, bytes) in cases {
+        assert_eq!(encode(&toml(text)), *bytes, "{}", text);
+        assert_decodes(bytes, &toml(utc));
+    }
+
+    // Local date-times have no instant.
+    for text in &["1979-05-27T07:32:00", "1979-05-27", "07:32:00"] {
+        assert_decodes(&encode(&toml(text)), &string(text));
+    }
+}
+
+#[test]
+fn extensions() {
+    // Other than timestamps
+    let ext = |ext_type: i128, data: &[i128]| {
+        map(vec![
+            ("type", Value::Int(ext_type)),
+            (
+                "data",
+                Value::Array(data.iter().map(|&b| Value::Int(b)).collect()),
+            ),
+        ])
+    };
+    assert_decodes(&[0xd4, 0x01, 0x2a], &ext(1, &[42]));
+    assert_decodes(&[0xd5, 0x80, 0x00, 0xff], &ext(-128, &[0, 255]));
+    assert_decodes(&[0xc7, 0x02, 0x05, 0x01, 0x02], &ext(5, &[1, 2]));
+    assert_decodes(&[0xc7, 0x00, 0x7f], &ext(127, &[]));
+    assert_decodes(
+        &[0x92, 0xc8, 0x00, 0x01, 0xfe, 0x03, 0xc0],
+        &Value::Array(vec![ext(-2, &[3]), Value::Null]),
+    );
+}
+
+#[test]
+fn errors() {
+    // Binary input is pointed by byte offsets.
+    let e = MsgpackParser.parse_bytes(&[0x92, 0xc0, 0xc1]).unwrap_err();
+    assert_eq!(e.offset(), 2);
+    assert_eq!(
+        e.to_string(),
+        "invalid MessagePack at byte 2: unexpected input"
+    );
+    let rendered = e.render("", "a.msgpack", false);
+    assert!(rendered.contains("a.msgpack, byte 2"), "{}", rendered);
+}
+
+#[test]
+fn syntax() {
+    let bytes = [
+        0x83, 0xa1, b'a', 0xc4, 0x02, 0x00, 0xff, 0x01, 0x90, 0xda, 0x00, 0x01, b'b', 0xde, 0x00,
+        0x01, 0xa1, b'c', 0xc0,
+    ];
+    let expected = JsonParser
+        .parse(r#"{"a": [0, 255], "1": [], "b": {"c": null}}"#)
+        .unwrap();
+    assert_decodes(&bytes, &expected);
+
+    let nested = [[0x91; 200].as_ref(), &[0xc0]].concat();
+    let cases: &[(&[u8], usize)] = &[
+        (&[], 0),
+        (&[0xc0, 0xc0], 1),
+        (&[0xc1], 0),
+        (&[0x92, 0xc0], 2),
+        (&[0xdd, 0xff, 0xff, 0xff, 0xff], 5),
+        (&[0xa2, b'a', 0xff], 2),
+        (&[0x81, 0xc0, 0xc0], 1),
+        (&[0x82, 0x01, 0xc0, 0xa1, b'1', 0xc0], 3),
+        (&[0xd4, 0x01], 2),
+        (&[0xc7, 0x02, 0x05, 0x01], 4),
+        (&[0xd5, 0xff, 0x00, 0x00], 0),
+        (&[0xd7, 0xff, 0xff, 0xff, 0xff, 0xfc, 0, 0, 0, 0], 0),
+        (
+            &[
+                0xc7, 12, 0xff, 0, 0, 0, 0, 0xff, 0xff, 0xff, 0xf1, 0x88, 0x6e, 0x08, 0xff,
+            ],
+            0,
+        ),
+        (&nested, 128),
+    ];
+    for (bytes, offset) in cases {
+        let e = MsgpackParser.parse_bytes(bytes).unwrap_err();
+        assert_eq!(e.offset(), *offset, "{:02x?}: {}", bytes, e);
+    }
+}
+
+#[test]
+fn text_bytes() {
+    let e = JsonParser.parse_bytes(b"[1,\n \"\xff\"]").unwrap_err();
+    assert_eq!((e.line(), e.column(), e.offset()), (2, 3, 6));
+    assert_eq!(e.expected(), ["UTF-8"]);
+    // Named by the format being parsed
+    assert_eq!(e.format(), "JSON");
+    assert_eq!(
+        TomlParser.parse_bytes(b"a = 1\xff").unwrap_err().format(),
+        "TOML"
+    );
+    assert_eq!(
+        YamlParser.parse_bytes(b"- \xc3").unwrap_err().format(),
+        "YAML"
+    );
+    let parsed = JsonParser.read_from(&mut &b"[\"\xc3\xa9\"]"[..]).unwrap();
+    assert_same(&parsed, &Value::Array(vec![string("é")]), "");
+}
@@ -0,0 +1,162 @@
+//! Tests of reading multiple documents, as `jfmt -M` does.
+mod common;
+
+use common::{assert_same, map, string};
+use df_utils::parser::{CombinedError, Json5Parser, JsonParser};
+use df_utils::Value;
+
+fn documents(text: &[u8], json5: bool) -> Result<Vec<Value>, CombinedError> {
+    if json5 {
+        Json5Parser.documents(text).collect()
+    } else {
+        JsonParser.documents(text).collect()
+    }
+}
+
+fn assert_documents(text: &[u8], json5: bool, expected: &[Value]) {
+    let documents = documents(text, json5).unwrap_or_else(|e| panic!("{}\n{:?}", e, text));
+    assert_same(
+        &Value::Array(documents),
+        &Value::Array(expected.to_vec()),
+        &String::from_utf8_lossy(text),
+    );
+}
+
+fn ints(is: &[i128]) -> Vec<Value> {
+    is.iter().map(|i| Value::Int(*i)).collect()
+}
+
+#[test]
+fn concatenated() {
+    assert_documents(b"", false, &[]);
+    assert_documents(b" \n", false, &[]);
+    assert_documents(b"1 2\n3", false, &ints(&[1, 2, 3]));
+    assert_documents(
+        b"{\"a\":1}{\"a\":2}[]\"b\"null",
+        false,
+        &[
+            map(vec![("a", Value::Int(1))]),
+            map(vec![("a", Value::Int(2))]),
+            Value::Array(vec![]),
+            string("b"),
+            Value::Null,
+        ],
+    );
+    // Duplicate keys are merged as `parse` does.
+    assert_documents(
+        b"{\"a\":1,\"b\":2,\"a\":3} 4",
+        false,
+        &[
+            map(vec![("a", Value::Int(3)), ("b", Value::Int(2))]),
+            Value::Int(4),
+        ],
+    );
+
+    // Errors end the iteration, after the documents before them.
+    let mut iter = JsonParser.documents(&b"1 [2,] 3"[..]);
+    assert!(matches!(iter.next(), Some(Ok(Value::Int(1)))));
+    match iter.next() {
+        Some(Err(CombinedError::Parse(e))) => assert_eq!((e.line(), e.column()), (1, 6)),
+        result => panic!("{:?}", result),
+    }
+    assert!(iter.next().is_none());
+    for text in &[&b"1 ["[..], b"1 }", b"\"a", b"1 \xff"] {
+        assert!(documents(text, false).is_err(), "{:?}", text);
+    }
+}
+
+#[test]
+fn record_separators() {
+    // RFC 7464
+    assert_documents(
+        b"\x1e1\n\x1e{\"a\":[true]}\n\x1e\"b\"\n",
+        false,
+        &[
+            Value::Int(1),
+            map(vec![("a", Value::Array(vec![Value::Boolean(true)]))]),
+            string("b"),
+        ],
+    );
+    assert_documents(b"\x1e\x1e[]\n\x1e", false, &[Value::Array(vec![])]);
+
+    // Truncated records are skipped, and the following ones are read.
+    for text in &[
+        &b"\x1e[1,\x1e2\n"[..],
+        b"\x1e{\"a\":\x1e2\n",
+        b"\x1e\"trunc\x1e2\n",
+        b"\x1e[1 x]\n\x1e2\n",
+        b"\x1e\xff\n\x1e2\n",
+        // Numbers at the root which whitespace doesn't follow
+        b"\x1e12\x1e2\n",
+        b"\x1e2\n\x1e3",
+    ] {
+        assert_documents(text, false, &ints(&[2]));
+    }
+    assert_documents(
+        b"\x1e1\n\x1e[\n\x1e2\n\x1e{\"a\"\n\x1e3\n",
+        false,
+        &ints(&[1, 2, 3]),
+    );
+    // Separators inside documents are errors without records.
+    assert!(documents(b"[1,\x1e2]", false).is_err());
+}
+
+#[test]
+fn json5() {
+    assert_documents(
+        b"{a: 1,} // one\n[2,]/* two */'three' +4 .5 0x6\n",
+        true,
+        &[
+            map(vec![("a", Value::Int(1))]),
+            Value::Array(vec![Value::Int(2)]),
+            string("three"),
+            Value::Int(4),
+            Value::Float(0.5),
+            Value::Int(6),
+        ],
+    );
+    assert_documents(
+        b"\x1eInfinity\n\x1e{a: [\x1e'b'\n",
+        true,
+        &[Value::Float(f64::INFINITY), string("b")],
+    );
+    assert!(documents(b"{a: 1} {b}", true).is_err());
+    // JSON5 isn't read as JSON.
+    assert!(documents(b"{a: 1}", false).is_err());
+}
+
+#[cfg(feature = "bin")]
+#[test]
+fn jfmt() {
+    use std::io::Write;
+    use std::process::{Command, Output, Stdio};
+
+    fn run(args: &[&str], input: &[u8]) -> Output {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_jfmt"))
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(input).unwrap();
+        child.wait_with_output().unwrap()
+    }
+
+    let output = run(&["-M", "-m"], b"{\"a\": 1} [2]\x1e\"c\"");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"{\"a\":1}\n[2]\n\"c\"\n");
+
+    let output = run(&["-M", "-m"], b"\x1e[1,\x1e2\n\x1e{\"a\":3}\n");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"2\n{\"a\":3}\n");
+
+    let output = run(&["-M", "-m", "--json5"], b"{a: 1,} 'b'");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"{\"a\":1}\n\"b\"\n");
+
+    // Documents before errors are written.
+    let output = run(&["-M", "-m"], b"1 2 x");
+    assert_eq!(output.status.code(), Some(4));
+    assert_eq!(output.stdout, b"1\n2\n");
+}
@@ -0,0 +1,99 @@
+//! Round trip tests between `NdjsonGenerator` and `NdjsonParser`.
+mod common;
+
+use common::{assert_same, datetimes, generate, map, string};
+use df_utils::generator::{MinJsonGenerator, NdjsonGenerator, PrettyJsonGenerator};
+use df_utils::parser::{CombinedError, JsonParser, NdjsonParser};
+use df_utils::{Parser, Value};
+
+fn round_trip(value: &Value) -> Value {
+    let text = generate(&NdjsonGenerator::new(), value);
+    NdjsonParser
+        .parse(&text)
+        .unwrap_or_else(|e| panic!("{}\n{}", e, text))
+}
+
+#[test]
+fn records() {
+    let value = Value::Array(vec![
+        Value::Null,
+        Value::Int(-1),
+        Value::Float(0.5),
+        Value::Boolean(true),
+        string("a\nb\"\\\u{1}"),
+        Value::Array(vec![Value::Int(1), Value::Array(vec![])]),
+        map(vec![("a", map(vec![])), ("b\nc", Value::Null)]),
+    ]);
+    assert_same(&round_trip(&value), &value, "");
+
+    let text = generate(&NdjsonGenerator::new(), &value);
+    assert_eq!(text.lines().count(), 7);
+    let records = NdjsonParser
+        .records(text.as_bytes())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_same(&Value::Array(records), &value, &text);
+}
+
+#[test]
+fn datetimes_as_strings() {
+    // Date-times are written as strings, as they are in JSON.
+    for d in datetimes() {
+        let value = Value::Array(vec![
+            d.clone(),
+            Value::Array(vec![d.clone()]),
+            map(vec![("d", d.clone())]),
+        ]);
+        let s = match &d {
+            Value::DateTime(d) => string(&d.to_string()),
+            _ => unreachable!(),
+        };
+        let expected = Value::Array(vec![
+            s.clone(),
+            Value::Array(vec![s.clone()]),
+            map(vec![("d", s)]),
+        ]);
+        assert_same(&round_trip(&value), &expected, &format!("{:?}", d));
+
+        for record in match &value {
+            Value::Array(vs) => vs,
+            _ => unreachable!(),
+        } {
+            let min = generate(&MinJsonGenerator::new(), record);
+            let pretty = generate(&PrettyJsonGenerator::new(), record);
+            assert_same(
+                &JsonParser.parse(&min).unwrap(),
+                &JsonParser.parse(&pretty).unwrap(),
+                &min,
+            );
+        }
+    }
+}
+
+#[test]
+fn errors() {
+    // Errors are of NDJSON, at the line of the record.
+    let e = NdjsonParser.parse("1\n\n[2,]\n").unwrap_err();
+    assert_eq!(e.format(), "NDJSON");
+    assert_eq!((e.line(), e.column(), e.offset()), (3, 4, 6));
+    let e = NdjsonParser.parse_bytes(b"1\n\"\xff\"\n").unwrap_err();
+    assert_eq!(e.format(), "NDJSON");
+
+    // Records are read independently, even after invalid UTF-8.
+    let mut records = NdjsonParser.records(&b"1\n[\"\xc3\"]\n[2,]\n3\n"[..]);
+    assert!(matches!(records.next(), Some(Ok(Value::Int(1)))));
+    for (line, column, offset) in &[(2, 3, 4), (3, 4, 11)] {
+        match records.next() {
+            Some(Err(CombinedError::Parse(e))) => {
+                assert_eq!(e.format(), "NDJSON");
+                assert_eq!(
+                    (e.line(), e.column(), e.offset()),
+                    (*line, *column, *offset)
+                );
+            }
+            record => panic!("{:?}", record),
+        }
+    }
+    assert!(matches!(records.next(), Some(Ok(Value::Int(3)))));
+    assert!(records.next().is_none());
+}
@@ -0,0 +1,130 @@
+//! Tests of `TomlGenerator`, checking the output and reading it again.
+mod common;
+
+use common::{assert_same, generate, map, string};
+use df_utils::generator::TomlGenerator;
+use df_utils::parser::TomlParser;
+use df_utils::{Generator, Map, Parser, Value};
+
+fn assert_generates(value: &Value, expected: &str) {
+    let text = generate(&TomlGenerator, value);
+    assert_eq!(text, expected);
+    let parsed = TomlParser
+        .parse(&text)
+        .unwrap_or_else(|e| panic!("{}\n{}", e, text));
+    assert_same(&parsed, value, &text);
+}
+
+#[test]
+fn tables() {
+    let value = map(vec![
+        ("title", string("TOML")),
+        ("a b", Value::Boolean(true)),
+        (
+            "mixed",
+            Value::Array(vec![
+                map(vec![("x", Value::Int(1))]),
+                Value::Int(2),
+                Value::Map(Map::new()),
+            ]),
+        ),
+        ("empty", Value::Array(Vec::new())),
+        (
+            "owner",
+            map(vec![
+                ("name", string("Tom")),
+                ("tags", Value::Array(vec![Value::Int(1), string("two")])),
+            ]),
+        ),
+        (
+            "servers",
+            map(vec![("alpha", map(vec![("ip", string("::1"))]))]),
+        ),
+        (
+            "products",
+            Value::Array(vec![
+                map(vec![("name", string("Hammer"))]),
+                Value::Map(Map::new()),
+            ]),
+        ),
+    ]);
+    assert_generates(
+        &value,
+        "title = \"TOML\"\n\
+         \"a b\" = true\n\
+         mixed = [{ x = 1 }, 2, {}]\n\
+         empty = []\n\
+         \n\
+         [owner]\n\
+         name = \"Tom\"\n\
+         tags = [1, \"two\"]\n\
+         \n\
+         [servers.alpha]\n\
+         ip = \"::1\"\n\
+         \n\
+         [[products]]\n\
+         name = \"Hammer\"\n\
+         \n\
+         [[products]]\n",
+    );
+    assert_generates(&Value::Map(Map::new()), "");
+}
+
+#[test]
+fn scalars() {
+    let value = map(vec![
+        ("max", Value::Int(i64::MAX.into())),
+        ("min", Value::Int(i64::MIN.into())),
+        ("float", Value::Float(1.0)),
+        ("small", Value::Float(1e-7)),
+        ("inf", Value::Float(f64::NEG_INFINITY)),
+        ("nan", Value::Float(f64::NAN)),
+        ("escape", string("\"\\\u{8}\t\n\u{c}\r\u{1}\u{7f}é")),
+        ("", string("")),
+    ]);
+    assert_generates(
+        &value,
+        "max = 9223372036854775807\n\
+         min = -9223372036854775808\n\
+         float = 1.0\n\
+         small = 1e-7\n\
+         inf = -inf\n\
+         nan = nan\n\
+         escape = \"\\\"\\\\\\b\\t\\n\\f\\r\\u0001\\u007Fé\"\n\
+         \"\" = \"\"\n",
+    );
+
+    let text = "a = 1979-05-27T07:32:00.999-08:00\nb = 07:32:00\n";
+    assert_generates(&TomlParser.parse(text).unwrap(), text);
+}
+
+#[test]
+fn invalid() {
+    for value in &[
+        Value::Int(1),
+        Value::Array(Vec::new()),
+        map(vec![("a", Value::Null)]),
+        map(vec![(
+            "a",
+            map(vec![("b", Value::Array(vec![Value::Null]))]),
+        )]),
+        map(vec![("a", Value::Int(i128::from(i64::MAX) + 1))]),
+        map(vec![("a", Value::Int(i128::from(i64::MIN) - 1))]),
+        map(vec![(
+            "a",
+            Value::Array(vec![map(vec![("b", Value::Int(u64::MAX.into()))])]),
+        )]),
+    ] {
+        let mut buf = Vec::new();
+        let e = TomlGenerator.generate(&mut buf, value).unwrap_err();
+        assert_eq!(e.kind(), std::io::ErrorKind::InvalidData, "{:?}", value);
+        assert!(buf.is_empty(), "{:?}", value);
+    }
+
+    let value = map(vec![("a", map(vec![("b", Value::Int(1 << 64))]))]);
+    let e = TomlGenerator.generate(&mut Vec::new(), &value).unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "TOML can't represent 18446744073709551616 out of 64 bit integers (at `a.b`)"
+    );
+}
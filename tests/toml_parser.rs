@@ -0,0 +1,232 @@
+//! Tests of `TomlParser` on hand-written documents.
+mod common;
+
+use common::{assert_same, map, string};
+use df_utils::parser::TomlParser;
+use df_utils::{Map, Parser, Value};
+
+fn parse(text: &str) -> Value {
+    TomlParser
+        .parse(text)
+        .unwrap_or_else(|e| panic!("{}\n{}", e, text))
+}
+
+/// Parses the value of `v = ...`.
+fn value(text: &str) -> Value {
+    match parse(&format!("v = {}", text)) {
+        Value::Map(mut m) => m.swap_remove("v").unwrap(),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn tables() {
+    let text = "\u{feff}# comment\n\
+                title = \"TOML\" # trailing comment\n\
+                site.\"google.com\" = true\n\
+                \n\
+                [owner]\n\
+                name = 'Tom'\n\
+                \n\
+                [servers . alpha]\n\
+                ip = \"10.0.0.1\"\r\n\
+                \n\
+                [[products]]\n\
+                name = \"Hammer\"\n\
+                \n\
+                [[products]]\n\
+                \n\
+                [[products]]\n\
+                color = { r = 1, g.b = 2 }\n\
+                \n\
+                [a.b]\n\
+                c = 1\n\
+                [a]\n\
+                d = 2\n";
+    let expected = map(vec![
+        ("title", string("TOML")),
+        ("site", map(vec![("google.com", Value::Boolean(true))])),
+        ("owner", map(vec![("name", string("Tom"))])),
+        (
+            "servers",
+            map(vec![("alpha", map(vec![("ip", string("10.0.0.1"))]))]),
+        ),
+        (
+            "products",
+            Value::Array(vec![
+                map(vec![("name", string("Hammer"))]),
+                Value::Map(Map::new()),
+                map(vec![(
+                    "color",
+                    map(vec![
+                        ("r", Value::Int(1)),
+                        ("g", map(vec![("b", Value::Int(2))])),
+                    ]),
+                )]),
+            ]),
+        ),
+        (
+            "a",
+            map(vec![
+                ("b", map(vec![("c", Value::Int(1))])),
+                ("d", Value::Int(2)),
+            ]),
+        ),
+    ]);
+    assert_same(&parse(text), &expected, text);
+    assert_same(&parse(""), &Value::Map(Map::new()), "");
+}
+
+#[test]
+fn strings() {
+    for (text, expected) in &[
+        (r#""a\tb\"\\\u00e9\U0001F600""#, "a\tb\"\\é😀"),
+        ("'C:\\path'", "C:\\path"),
+        ("\"\"\"\nline\r\nbreak\"\"\"", "line\nbreak"),
+        ("\"\"\"a \\\n\n   b\"\"\"", "a b"),
+        ("\"\"\"\"quoted\"\"\"\"", "\"quoted\""),
+        ("'''\nraw\\n'''", "raw\\n"),
+        ("''''a'''''", "'a''"),
+        ("\"\"", ""),
+    ] {
+        assert_same(&value(text), &string(expected), text);
+    }
+}
+
+#[test]
+fn numbers() {
+    for (text, expected) in &[
+        ("+99", 99),
+        ("-17", -17),
+        ("0", 0),
+        ("1_000", 1000),
+        ("0xDEAD_beef", 0xdead_beef),
+        ("0o755", 0o755),
+        ("0b1101", 0b1101),
+        ("9223372036854775807", i64::MAX.into()),
+        ("-9223372036854775808", i64::MIN.into()),
+        ("0x7fffffffffffffff", i64::MAX.into()),
+    ] {
+        assert_same(&value(text), &Value::Int(*expected), text);
+    }
+    for (text, expected) in &[
+        ("1.5", 1.5),
+        ("-0.01", -0.01),
+        ("5e+22", 5e22),
+        ("1e06", 1e6),
+        ("6.626e-34", 6.626e-34),
+        ("9_224_617.445_991", 9_224_617.445_991),
+        ("inf", f64::INFINITY),
+        ("-inf", f64::NEG_INFINITY),
+        ("nan", f64::NAN),
+    ] {
+        assert_same(&value(text), &Value::Float(*expected), text);
+    }
+}
+
+#[test]
+fn datetimes() {
+    for (text, expected) in &[
+        ("1979-05-27T07:32:00Z", "1979-05-27T07:32:00Z"),
+        ("1979-05-27 07:32:00-07:00", "1979-05-27T07:32:00-07:00"),
+        (
+            "1979-05-27t00:32:00.999999+09:30",
+            "1979-05-27T00:32:00.999999+09:30",
+        ),
+        ("1979-05-27T00:32:00", "1979-05-27T00:32:00"),
+        ("1979-05-27", "1979-05-27"),
+        ("07:32:00", "07:32:00"),
+        // Digits beyond nanoseconds are truncated.
+        ("00:32:00.1234567899", "00:32:00.123456789"),
+        ("2000-02-29", "2000-02-29"),
+    ] {
+        match value(text) {
+            Value::DateTime(d) => assert_eq!(d.to_string(), *expected),
+            v => panic!("{:?}", v),
+        }
+    }
+}
+
+#[test]
+fn arrays() {
+    let text = "[\n  1, # one\n  [\"a\", 'b'],\n  { x = [] },\n]";
+    let expected = Value::Array(vec![
+        Value::Int(1),
+        Value::Array(vec![string("a"), string("b")]),
+        map(vec![("x", Value::Array(Vec::new()))]),
+    ]);
+    assert_same(&value(text), &expected, text);
+}
+
+#[test]
+fn invalid() {
+    for text in &[
+        // Keys and tables
+        "a = 1\na = 2",
+        "a.b = 1\na = 2",
+        "a = 1\na.b = 2",
+        "[a]\n[a]",
+        "[a]\nb = 1\n[a.b]",
+        "a = {}\n[a]",
+        "a = { b = 1 }\na.c = 2",
+        "[[a]]\n[a]",
+        "a = []\n[[a]]",
+        "a b = 1",
+        "= 1",
+        "a =",
+        "a = 1 b = 2",
+        "[a]b = 1",
+        "[]",
+        // Integers
+        "a = 9223372036854775808",
+        "a = -9223372036854775809",
+        "a = 0x8000000000000000",
+        "a = 0b1_0000000000000000000000000000000000000000000000000000000000000000",
+        "a = 01",
+        "a = 1__0",
+        "a = _1",
+        "a = 1_",
+        "a = 0x_1",
+        "a = 0X1",
+        "a = +0x1",
+        // Floats
+        "a = .5",
+        "a = 1.",
+        "a = 1.e5",
+        "a = 1e",
+        "a = 01.5",
+        "a = NaN",
+        // Strings
+        "a = \"unclosed",
+        "a = \"line\nbreak\"",
+        "a = \"\\x41\"",
+        "a = \"\\uD800\"",
+        "a = \"\u{1}\"",
+        "a = 'unclosed",
+        "a = \"\"\"a\"\"\"\"\"\"",
+        // Date-times
+        "a = 2021-02-29",
+        "a = 2021-13-01",
+        "a = 0000-01-01",
+        "a = 24:00:00",
+        "a = 12:60:00",
+        "a = 1979-05-27T07:32:00+15:00",
+        "a = 1979-05-27T07:32",
+        "a = 07:32:00Z",
+        // Others
+        "a = [1 2]",
+        "a = { b = 1, }",
+        "a = { b = 1\n}",
+        "a = true # \u{0}",
+        "a = True",
+    ] {
+        assert!(TomlParser.parse(text).is_err(), "{:?}", text);
+    }
+
+    let e = TomlParser
+        .parse("a = 1\nb = 99999999999999999999")
+        .unwrap_err();
+    assert_eq!(e.line(), 2);
+    let e = TomlParser.parse("a = 1\n[b]\na = 1\na = 2").unwrap_err();
+    assert_eq!((e.line(), e.column()), (4, 1));
+}
@@ -0,0 +1,134 @@
+//! Tests of the writers of generators, and of the checks of their calls.
+mod common;
+
+use common::{generate, map, string};
+use df_utils::generator::{LineGenerator, MinJsonGenerator, PrettyJsonGenerator, Writer};
+use df_utils::Value;
+use std::io;
+
+#[derive(Copy, Clone, Debug)]
+enum Call {
+    BeginArray,
+    EndArray,
+    BeginObject,
+    Key,
+    EndObject,
+    Value,
+    Finish,
+}
+use Call::*;
+
+/// Makes the calls to the writer, returning the error of the first failing one.
+fn calls<V: Writer>(mut writer: V, calls: &[Call]) -> io::Result<()> {
+    for call in calls {
+        match call {
+            BeginArray => writer.begin_array()?,
+            EndArray => writer.end_array()?,
+            BeginObject => writer.begin_object()?,
+            Key => writer.key("a")?,
+            EndObject => writer.end_object()?,
+            Value => writer.value(&Value::Int(1))?,
+            Finish => return writer.finish(),
+        }
+    }
+    Ok(())
+}
+
+/// Asserts that the calls fail with `InvalidInput` with every writer.
+fn assert_misuse(sequence: &[Call]) {
+    let line = LineGenerator::new();
+    let min = MinJsonGenerator::new();
+    let pretty = PrettyJsonGenerator::new();
+    for result in [
+        calls(line.writer(Vec::new()), sequence),
+        calls(min.writer(Vec::new()), sequence),
+        calls(pretty.writer(Vec::new()), sequence),
+    ]
+    .iter()
+    {
+        match result {
+            Err(e) => assert_eq!(e.kind(), io::ErrorKind::InvalidInput, "{:?}", sequence),
+            Ok(()) => panic!("{:?}", sequence),
+        }
+    }
+}
+
+#[test]
+fn misuse() {
+    // Closing without opening
+    assert_misuse(&[EndArray]);
+    assert_misuse(&[EndObject]);
+    assert_misuse(&[BeginArray, EndObject]);
+    assert_misuse(&[BeginObject, EndArray]);
+    assert_misuse(&[BeginArray, EndArray, EndArray]);
+    // Keys outside of objects
+    assert_misuse(&[Key]);
+    assert_misuse(&[BeginArray, Key]);
+    assert_misuse(&[BeginObject, BeginArray, Key]);
+    assert_misuse(&[BeginArray, BeginArray, EndArray, Key]);
+    // Keys and values out of turn
+    assert_misuse(&[BeginObject, Value]);
+    assert_misuse(&[BeginObject, Key, Key]);
+    assert_misuse(&[BeginObject, Key, EndObject]);
+    assert_misuse(&[BeginObject, Key, Value, Value]);
+    // More than one value
+    assert_misuse(&[Value, Value]);
+    assert_misuse(&[BeginArray, EndArray, BeginObject]);
+    // Incomplete values
+    assert_misuse(&[Finish]);
+    assert_misuse(&[BeginArray, Finish]);
+    assert_misuse(&[BeginObject, Key, Finish]);
+    assert_misuse(&[BeginArray, BeginObject, Key, Value, EndObject, Finish]);
+}
+
+#[test]
+fn writing() {
+    let value = map(vec![
+        ("a", Value::Array(vec![Value::Int(1), map(vec![])])),
+        (
+            "b",
+            map(vec![("c", string("d")), ("e", Value::Array(vec![]))]),
+        ),
+        ("f", Value::Null),
+    ]);
+    // Writes the same value by calls, and by parts of it.
+    fn by_calls<V: Writer>(mut writer: V) {
+        writer.begin_object().unwrap();
+        writer.key("a").unwrap();
+        writer.begin_array().unwrap();
+        writer.value(&Value::Int(1)).unwrap();
+        writer.begin_object().unwrap();
+        writer.end_object().unwrap();
+        writer.end_array().unwrap();
+        writer.key("b").unwrap();
+        writer
+            .value(&map(vec![("c", string("d")), ("e", Value::Array(vec![]))]))
+            .unwrap();
+        writer.key("f").unwrap();
+        writer.value(&Value::Null).unwrap();
+        writer.end_object().unwrap();
+        writer.finish().unwrap();
+    }
+
+    let line = LineGenerator::new();
+    let mut buf = Vec::new();
+    by_calls(line.writer(&mut buf));
+    assert_eq!(String::from_utf8(buf).unwrap(), generate(&line, &value));
+
+    let min = MinJsonGenerator::new();
+    let mut buf = Vec::new();
+    by_calls(min.writer(&mut buf));
+    assert_eq!(String::from_utf8(buf).unwrap(), generate(&min, &value));
+
+    let pretty = PrettyJsonGenerator::new();
+    let mut buf = Vec::new();
+    by_calls(pretty.writer(&mut buf));
+    assert_eq!(String::from_utf8(buf).unwrap(), generate(&pretty, &value));
+
+    // A scalar as the whole value
+    let mut buf = Vec::new();
+    let mut writer = min.writer(&mut buf);
+    writer.value(&string("a")).unwrap();
+    writer.finish().unwrap();
+    assert_eq!(buf, b"\"a\"");
+}
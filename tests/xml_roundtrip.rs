@@ -0,0 +1,200 @@
+//! Round trip tests between `XmlGenerator` and `XmlParser`.
+mod common;
+
+use common::{assert_same, generate, map, string};
+use df_utils::generator::XmlGenerator;
+use df_utils::parser::XmlParser;
+use df_utils::{Generator, Map, Parser, Value};
+
+fn assert_round_trip(parser: &XmlParser, value: &Value) {
+    for indent in &[0, 2, 4] {
+        let text = generate(XmlGenerator::new().indent(*indent), value);
+        let parsed = parser
+            .parse(&text)
+            .unwrap_or_else(|e| panic!("{}\n{}", e, text));
+        assert_same(&parsed, value, &text);
+    }
+}
+
+/// Strings which need escaping or could be normalized.
+const STRINGS: &[&str] = &[
+    "plain",
+    " ",
+    " padded ",
+    "<tag>",
+    "a & b",
+    "&amp;",
+    "]]>",
+    "\"'",
+    "\t",
+    "line\nbreak",
+    "\r\n",
+    "\r",
+    "\u{85}\u{2028}",
+    "日本語",
+    "😀",
+];
+
+#[test]
+fn strings() {
+    let parser = XmlParser::new();
+    for s in STRINGS {
+        assert_round_trip(&parser, &map(vec![("r", string(s))]));
+        assert_round_trip(&parser, &map(vec![("r", map(vec![("@a", string(s))]))]));
+        assert_round_trip(
+            &parser,
+            &map(vec![(
+                "r",
+                map(vec![("@a", string(s)), ("#text", string(s))]),
+            )]),
+        );
+        assert_round_trip(&parser, &map(vec![("r", map(vec![("c", string(s))]))]));
+    }
+}
+
+#[test]
+fn elements() {
+    let value = map(vec![(
+        "soap:Envelope",
+        map(vec![
+            (
+                "@xmlns:soap",
+                string("http://schemas.xmlsoap.org/soap/envelope/"),
+            ),
+            (
+                "soap:Body",
+                map(vec![
+                    (
+                        "item",
+                        Value::Array(vec![string("a"), Value::Null, string("c")]),
+                    ),
+                    ("empty", Value::Null),
+                    (
+                        "mixed",
+                        map(vec![("b", string("bold")), ("#text", string("text"))]),
+                    ),
+                ]),
+            ),
+        ]),
+    )]);
+    assert_round_trip(&XmlParser::new(), &value);
+    assert_eq!(
+        generate(&XmlGenerator::new(), &value),
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <soap:Envelope xmlns:soap=\"http://schemas.xmlsoap.org/soap/envelope/\">\n  \
+           <soap:Body>\n    \
+             <item>a</item>\n    \
+             <item/>\n    \
+             <item>c</item>\n    \
+             <empty/>\n    \
+             <mixed>text<b>bold</b></mixed>\n  \
+           </soap:Body>\n\
+         </soap:Envelope>\n"
+    );
+
+    let arrays = map(vec![(
+        "r",
+        map(vec![
+            ("a", Value::Array(vec![string("1")])),
+            (
+                "b",
+                Value::Array(vec![map(vec![("c", Value::Array(vec![Value::Null]))])]),
+            ),
+        ]),
+    )]);
+    let mut parser = XmlParser::new();
+    assert_round_trip(parser.always_array(true), &arrays);
+}
+
+#[test]
+fn syntax() {
+    let text = "\u{feff}<?xml version=\"1.0\"?>\r\n\
+                <!DOCTYPE r SYSTEM \"r.dtd\" [\n\
+                  <!ELEMENT r ANY>\n\
+                  <!ATTLIST r a CDATA \"x>y\">\n\
+                  <!ENTITY who \"W &#38;#38; &name;\">\n\
+                  <!ENTITY name 'N'>\n\
+                  <!ENTITY name 'ignored'>\n\
+                  <!ENTITY % p \"p\">\n\
+                  %p;\n\
+                ]>\n\
+                <!-- comment -->\n\
+                <r a = 'tab\tand\r\nline' b=\"&lt;&#x3C;&#60;\">\
+                <?pi data?><!-- c --><t>x<![CDATA[<&>]]>&who;\r\n</t></r >\n";
+    let expected = map(vec![(
+        "r",
+        map(vec![
+            ("@a", string("tab and line")),
+            ("@b", string("<<<")),
+            ("t", string("x<&>W & N\n")),
+        ]),
+    )]);
+    assert_same(&XmlParser::new().parse(text).unwrap(), &expected, text);
+
+    for text in &[
+        "",
+        "<a>",
+        "<a></b>",
+        "<a/><b/>",
+        "text",
+        "<a x='1' x='2'/>",
+        "<a x=1/>",
+        "<a x='<'/>",
+        "<p:a/>",
+        "<a p:x='1'/>",
+        "<a:b:c xmlns:a='a'/>",
+        "<a>&nope;</a>",
+        "<a>&#0;</a>",
+        "<a>\u{1}</a>",
+        "<a>&#xD800;</a>",
+        "<!DOCTYPE a [<!ENTITY e \"&e;\">]><a>&e;</a>",
+        "<!DOCTYPE a [<!ENTITY e SYSTEM \"e.xml\">]><a>&e;</a>",
+        "<a><![CDATA[x</a>",
+        "<a><!-- x</a>",
+    ] {
+        assert!(XmlParser::new().parse(text).is_err(), "{:?}", text);
+    }
+    assert!(XmlParser::new()
+        .parse("<a xmlns:p='p'><p:b p:c=''/></a>")
+        .is_ok());
+}
+
+#[test]
+fn invalid() {
+    for value in &[
+        Value::Null,
+        Value::Map(Map::new()),
+        map(vec![("a", Value::Null), ("b", Value::Null)]),
+        map(vec![("a", Value::Array(vec![]))]),
+        map(vec![("1a", Value::Null)]),
+        map(vec![("a", map(vec![("@", Value::Null)]))]),
+        map(vec![("a", map(vec![("@b", Value::Array(vec![]))]))]),
+        map(vec![(
+            "a",
+            map(vec![("b", Value::Array(vec![Value::Array(vec![])]))]),
+        )]),
+        map(vec![("a", string("\0"))]),
+    ] {
+        let result = XmlGenerator::new().generate(&mut Vec::new(), value);
+        assert!(result.is_err(), "{:?}", value);
+    }
+}
+
+#[test]
+fn limits() {
+    let nested = |depth: usize| format!("{}{}", "<a>".repeat(depth), "</a>".repeat(depth));
+    assert!(XmlParser::new().parse(&nested(128)).is_ok());
+    for depth in &[129, 50_000] {
+        let e = XmlParser::new().parse(&nested(*depth)).unwrap_err();
+        assert_eq!(e.expected(), ["nesting within limits"]);
+        assert_eq!(e.column(), 128 * 3 + 1);
+    }
+}
+
+#[test]
+fn entity_markup() {
+    // Replacement text of entities is read as text, not as markup.
+    let text = "<!DOCTYPE a [<!ENTITY e \"<b>x</b>&amp;\">]><a>&e;</a>";
+    let expected = map(vec![("a", string("<b>x</b>&"))]);
+    assert_same(&XmlParser::new().parse(text).unwrap(), &expected, text);
+}
@@ -0,0 +1,410 @@
+//! Tests of `YamlParser` parsing texts written by hand.
+mod common;
+
+use common::{assert_same, map, string};
+use df_utils::parser::{TomlParser, YamlParser};
+use df_utils::{Parser, Value};
+
+fn parse(text: &str) -> Value {
+    YamlParser
+        .parse(text)
+        .unwrap_or_else(|e| panic!("{}\n{}", e, text))
+}
+
+fn assert_parses(text: &str, expected: &Value) {
+    assert_same(&parse(text), expected, text);
+}
+
+fn strings(ss: &[&str]) -> Value {
+    Value::Array(ss.iter().map(|s| string(s)).collect())
+}
+
+/// Parses a TOML value, to make date-times.
+fn toml(s: &str) -> Value {
+    match TomlParser.parse(&format!("v = {}", s)).unwrap() {
+        Value::Map(mut m) => m.swap_remove("v").unwrap(),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn anchors() {
+    assert_parses(
+        "a: &x [1, 2]\nb: *x\nc: &y\n  d: e\nf: *y\n",
+        &map(vec![
+            ("a", Value::Array(vec![Value::Int(1), Value::Int(2)])),
+            ("b", Value::Array(vec![Value::Int(1), Value::Int(2)])),
+            ("c", map(vec![("d", string("e"))])),
+            ("f", map(vec![("d", string("e"))])),
+        ]),
+    );
+    // Anchors can be redefined, and aliases refer to the latest ones.
+    assert_parses(
+        "- &a 1\n- *a\n- &a 2\n- *a",
+        &Value::Array(vec![
+            Value::Int(1),
+            Value::Int(1),
+            Value::Int(2),
+            Value::Int(2),
+        ]),
+    );
+    assert_parses(
+        "[&a x, *a, {b: *a}]",
+        &Value::Array(vec![
+            string("x"),
+            string("x"),
+            map(vec![("b", string("x"))]),
+        ]),
+    );
+}
+
+#[test]
+fn merge_keys() {
+    let text = "\
+base: &base
+  a: 1
+  b: 2
+other: &other
+  c: 3
+one:
+  <<: *base
+  b: 20
+many:
+  <<: [*base, *other]
+  d: 4
+flow: {<<: *other, a: 0}
+";
+    let expected = map(vec![
+        (
+            "base",
+            map(vec![("a", Value::Int(1)), ("b", Value::Int(2))]),
+        ),
+        ("other", map(vec![("c", Value::Int(3))])),
+        (
+            "one",
+            map(vec![("a", Value::Int(1)), ("b", Value::Int(20))]),
+        ),
+        (
+            "many",
+            map(vec![
+                ("a", Value::Int(1)),
+                ("b", Value::Int(2)),
+                ("c", Value::Int(3)),
+                ("d", Value::Int(4)),
+            ]),
+        ),
+        (
+            "flow",
+            map(vec![("c", Value::Int(3)), ("a", Value::Int(0))]),
+        ),
+    ]);
+    assert_parses(text, &expected);
+    // Quoted `<<` is an ordinary key.
+    assert_parses("'<<': 1", &map(vec![("<<", Value::Int(1))]));
+}
+
+#[test]
+fn flow_collections() {
+    assert_parses(
+        "[a, 'b', \"c\", [], {}, [1, [2]], {d: e, f: [g]},]",
+        &Value::Array(vec![
+            string("a"),
+            string("b"),
+            string("c"),
+            Value::Array(vec![]),
+            map(vec![]),
+            Value::Array(vec![Value::Int(1), Value::Array(vec![Value::Int(2)])]),
+            map(vec![("d", string("e")), ("f", strings(&["g"]))]),
+        ]),
+    );
+    assert_parses(
+        "{a: 1,\n  b c: [x,\n y],\n\"d\":2, e, f: }",
+        &map(vec![
+            ("a", Value::Int(1)),
+            ("b c", strings(&["x", "y"])),
+            ("d", Value::Int(2)),
+            ("e", Value::Null),
+            ("f", Value::Null),
+        ]),
+    );
+    // Pairs in sequences are single pair mappings.
+    assert_parses(
+        "[a: 1, ? b : 2, c]",
+        &Value::Array(vec![
+            map(vec![("a", Value::Int(1))]),
+            map(vec![("b", Value::Int(2))]),
+            string("c"),
+        ]),
+    );
+    // `:` and `-` can be in plain scalars.
+    assert_parses("[a:b, -c, http://x]", &strings(&["a:b", "-c", "http://x"]));
+}
+
+#[test]
+fn complex_keys() {
+    assert_parses(
+        "? a\n: 1\n? b\n? 'c\n  d'\n: - 2\n  - 3\ne: 4\n",
+        &map(vec![
+            ("a", Value::Int(1)),
+            ("b", Value::Null),
+            ("c d", Value::Array(vec![Value::Int(2), Value::Int(3)])),
+            ("e", Value::Int(4)),
+        ]),
+    );
+    assert_parses(
+        "- ? a\n  : b\n",
+        &Value::Array(vec![map(vec![("a", string("b"))])]),
+    );
+}
+
+#[test]
+fn block_scalars() {
+    for (text, expected) in &[
+        // Clipping keeps a single line break.
+        ("|\n  a\n   b\n\n", "a\n b\n"),
+        ("|-\n  a\n  b\n\n", "a\nb"),
+        ("|+\n  a\n  b\n\n", "a\nb\n\n"),
+        // Folding joins lines, except more indented and empty ones.
+        (">\n  a\n  b\n\n  c\n    d\n  e\n", "a b\nc\n  d\ne\n"),
+        (">-\n  a\n  b\n", "a b"),
+        (">+\n  a\n\n", "a\n\n"),
+        // Indentation indicators
+        ("|2\n   a\n  b\n", " a\nb\n"),
+        ("|1-\n  a\n", " a"),
+        ("|\n\n  a\n", "\na\n"),
+        ("|\n", ""),
+        ("|+\n\n", "\n"),
+        ("| # comment\n  a\n", "a\n"),
+    ] {
+        assert_parses(text, &string(expected));
+    }
+    assert_parses(
+        "a: |\n  x\n  y\nb: >-\n  z\n  w\n",
+        &map(vec![("a", string("x\ny\n")), ("b", string("z w"))]),
+    );
+}
+
+#[test]
+fn documents() {
+    let text = "\
+%YAML 1.2
+---
+a: 1
+...
+--- 2
+---
+- x
+---
+";
+    let documents = YamlParser.documents(text).unwrap();
+    assert_same(
+        &Value::Array(documents),
+        &Value::Array(vec![
+            map(vec![("a", Value::Int(1))]),
+            Value::Int(2),
+            strings(&["x"]),
+            Value::Null,
+        ]),
+        text,
+    );
+    assert_eq!(YamlParser.documents("").unwrap().len(), 0);
+    assert_eq!(YamlParser.documents("# comment\n").unwrap().len(), 0);
+    // Anchors are local to documents.
+    assert!(YamlParser.documents("--- &a 1\n--- *a\n").is_err());
+
+    // `parse` takes a single document.
+    assert_parses("--- 1\n...\n", &Value::Int(1));
+    assert_parses("", &Value::Null);
+    let e = YamlParser.parse("--- 1\n--- 2\n").unwrap_err();
+    assert_eq!((e.line(), e.column()), (2, 1));
+}
+
+#[test]
+fn directives_and_tags() {
+    assert_parses(
+        "%YAML 1.2\n%TAG !e! tag:example.com,2000:\n--- !!map\na: 1\n",
+        &map(vec![("a", Value::Int(1))]),
+    );
+    assert_parses(
+        "\
+- !!str 1
+- !!int '2'
+- !!float 3
+- !!bool 'true'
+- !!null ''
+- !!str
+- !<tag:yaml.org,2002:int> \"4\"
+- !local 5
+- ! 6
+- !!timestamp 2001-12-14
+",
+        &Value::Array(vec![
+            string("1"),
+            Value::Int(2),
+            Value::Float(3.0),
+            Value::Boolean(true),
+            Value::Null,
+            string(""),
+            Value::Int(4),
+            string("5"),
+            string("6"),
+            toml("2001-12-14"),
+        ]),
+    );
+    assert_parses(
+        "!!map {a: !!seq [!!str b]}",
+        &map(vec![("a", strings(&["b"]))]),
+    );
+}
+
+#[test]
+fn scalars() {
+    assert_parses(
+        "[~, null, true, False, 0o17, 0x1f, -12, 1_000, 1.5, .inf, -.Inf, 1e3, yes, '1', \"a\\tb\\u00e9\"]",
+        &Value::Array(vec![
+            Value::Null,
+            Value::Null,
+            Value::Boolean(true),
+            Value::Boolean(false),
+            Value::Int(15),
+            Value::Int(31),
+            Value::Int(-12),
+            string("1_000"),
+            Value::Float(1.5),
+            Value::Float(f64::INFINITY),
+            Value::Float(f64::NEG_INFINITY),
+            Value::Float(1000.0),
+            string("yes"),
+            string("1"),
+            string("a\tb\u{e9}"),
+        ]),
+    );
+    // Multi-line scalars are folded.
+    assert_parses(
+        "a: b\n  c\n\n  d\ne: 'f\n  g'\n",
+        &map(vec![("a", string("b c\nd")), ("e", string("f g"))]),
+    );
+}
+
+#[test]
+fn timestamps() {
+    for (text, expected) in &[
+        (
+            "2001-12-14t21:59:43.10-05:00",
+            "2001-12-14T21:59:43.10-05:00",
+        ),
+        (
+            "2001-12-14T21:59:43.10-05:00",
+            "2001-12-14T21:59:43.10-05:00",
+        ),
+        ("2001-12-14 21:59:43.10 -5", "2001-12-14T21:59:43.10-05:00"),
+        ("2001-12-15 2:59:43.10", "2001-12-15T02:59:43.10"),
+        ("2001-12-15T02:59:43.1Z", "2001-12-15T02:59:43.1Z"),
+        ("2001-12-14 21:59:43 +09:30", "2001-12-14T21:59:43+09:30"),
+        ("2002-12-14", "2002-12-14"),
+        ("2001-1-2 3:04:05", "2001-01-02T03:04:05"),
+    ] {
+        assert_parses(text, &toml(expected));
+    }
+    // Otherwise strings
+    for text in &[
+        "2001-1-2",
+        "2001-12-14 21:59",
+        "2001-13-01",
+        "2001-02-30",
+        "2001-12-14x21:59:43",
+        "2001-12-14 25:00:00",
+        "2001-12-14 21:59:43 +5:3",
+    ] {
+        assert_parses(text, &string(text));
+    }
+}
+
+#[test]
+fn columns() {
+    // Columns are counted in characters, not in bytes.
+    assert_parses(
+        "- &\u{e4} a: 1\n     b: 2\n- *\u{e4}\n",
+        &Value::Array(vec![
+            map(vec![("a", Value::Int(1)), ("b", Value::Int(2))]),
+            map(vec![("a", Value::Int(1)), ("b", Value::Int(2))]),
+        ]),
+    );
+    let e = YamlParser.parse("\u{e9}: [1,\n\u{e9}").unwrap_err();
+    assert_eq!((e.line(), e.column()), (2, 2));
+}
+
+#[test]
+fn errors() {
+    for (text, expected, at) in &[
+        // Undefined aliases
+        ("a: *b", "defined anchor", (1, 4)),
+        ("- &a 1\n- *b", "defined anchor", (2, 3)),
+        ("[*a, &a 1]", "defined anchor", (1, 2)),
+        // Duplicate keys
+        ("a: 1\nb: 2\na: 3", "unique key", (3, 1)),
+        ("{a: 1, a: 2}", "unique key", (1, 8)),
+        ("? a\n? a", "unique key", (2, 3)),
+        // Bad indentation
+        ("a:\n  b: 1\n c: 2", "mapping key", (3, 2)),
+        ("a: 1\n  b: 2", "mapping key", (2, 3)),
+        ("- [1]\n  - 2", "sequence entry", (2, 3)),
+        ("a:\n  - 1\n - 2", "mapping key", (3, 2)),
+        ("a: b: c", "line break", (1, 4)),
+        ("- - 1\n - 2", "sequence entry", (2, 2)),
+        // Others
+        ("[1, 2", "\"]\"", (1, 6)),
+        ("{a: 1", "\"}\"", (1, 6)),
+        ("'a", "\"'\"", (1, 3)),
+        ("\"\\q\"", "escape", (1, 3)),
+        ("!!int a", "integer", (1, 7)),
+        ("<<: 1", "mapping to merge", (1, 1)),
+        // Keys must be scalars.
+        ("? [a]\n: 1", "scalar key", (1, 3)),
+        ("? |\n  a\n: 1", "scalar key", (1, 3)),
+    ] {
+        let e = YamlParser.parse(text).unwrap_err();
+        assert!(e.expected().contains(expected), "{:?} {:?}", text, e);
+        assert_eq!((e.line(), e.column()), *at, "{:?}", text);
+    }
+}
+
+#[test]
+fn limits() {
+    // Collections can be nested up to 128 levels.
+    let flow = |n| format!("{}{}", "[".repeat(n), "]".repeat(n));
+    let block = |n: usize| {
+        (0..n)
+            .map(|i| format!("{}a:\n", "  ".repeat(i)))
+            .collect::<String>()
+    };
+    for text in &[flow(128), block(127), format!("{}1", "- ".repeat(128))] {
+        assert!(YamlParser.parse(text).is_ok(), "{}", text);
+    }
+    for (text, column) in &[
+        (flow(129), 129),
+        (flow(200_000), 129),
+        ("[{a: ".repeat(100), 321),
+        (format!("{}1", "- ".repeat(129)), 257),
+    ] {
+        let e = YamlParser.parse(text).unwrap_err();
+        assert_eq!(e.expected(), ["nesting within limits"]);
+        assert_eq!(e.column(), *column);
+    }
+    let e = YamlParser.parse(&block(129)).unwrap_err();
+    assert_eq!(
+        (e.line(), e.expected()),
+        (129, &["nesting within limits"][..])
+    );
+
+    // Aliases can't expand exponentially, while 100,000 copies of a scalar can.
+    let mut bomb = "a0: &a0 [x, x, x, x, x, x, x, x, x, x]\n".to_string();
+    for i in 1..=7 {
+        let a = format!("*a{}", i - 1);
+        bomb += &format!("a{}: &a{} [{}]\n", i, i, vec![a; 10].join(", "));
+    }
+    let e = YamlParser.parse(&bomb).unwrap_err();
+    assert_eq!(e.expected(), ["alias expansion within limits"]);
+    let text = bomb.rsplitn(5, '\n').last().unwrap();
+    YamlParser.parse(text).unwrap_or_else(|e| panic!("{}", e));
+}
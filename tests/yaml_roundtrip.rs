@@ -0,0 +1,232 @@
+//! Round trip tests between `YamlGenerator` and `YamlParser`.
+mod common;
+
+use common::{map, same, Rng};
+use df_utils::generator::YamlGenerator;
+use df_utils::parser::YamlParser;
+use df_utils::{Generator, Map, Parser, Value};
+
+fn generate(generator: &YamlGenerator, value: &Value) -> String {
+    let mut buf = Vec::new();
+    generator.generate(&mut buf, value).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+fn assert_round_trip(generator: &YamlGenerator, value: &Value) {
+    let text = generate(generator, value);
+    let parsed = YamlParser
+        .parse(&text)
+        .unwrap_or_else(|e| panic!("{}\n{}", e, text));
+    assert!(same(value, &parsed), "{:?}\n{}\n{:?}", value, text, parsed);
+}
+
+fn generators() -> Vec<YamlGenerator> {
+    [2, 4, 9]
+        .iter()
+        .map(|i| {
+            let mut generator = YamlGenerator::new();
+            generator.indent(*i);
+            generator
+        })
+        .collect()
+}
+
+/// Date-times except local times, which YAML doesn't have.
+fn datetimes() -> Vec<Value> {
+    let mut datetimes = common::datetimes();
+    datetimes.pop();
+    datetimes
+}
+
+/// Strings which could be misread as other types or as the syntax.
+const STRINGS: &[&str] = &[
+    "",
+    "plain",
+    "yes",
+    "No",
+    "off",
+    "y",
+    "null",
+    "~",
+    "true",
+    "0",
+    "-1",
+    "1e3",
+    "1.5",
+    ".inf",
+    ".nan",
+    "0x1F",
+    "0o17",
+    "1_000",
+    "12:30",
+    "2021-01-01",
+    "2001-12-14 21:59:43.10 -5",
+    "-",
+    "- a",
+    "-a",
+    "? a",
+    ":",
+    "a: b",
+    "a:",
+    "a:b",
+    "#",
+    "a #b",
+    "a#b",
+    "&a",
+    "*a",
+    "!a",
+    "|",
+    ">",
+    "'",
+    "\"",
+    "%",
+    "@",
+    "`",
+    "[]",
+    "{}",
+    "a, b",
+    "<<",
+    "=",
+    "---",
+    "...",
+    " ",
+    " lead",
+    "trail ",
+    "\t",
+    "line\nbreak",
+    "trailing\n",
+    "two trailing\n\n",
+    "\n",
+    "\n\nlead",
+    "\n  lead spaces\n",
+    "  indented\nlines\n",
+    "a\n\n\nb",
+    "a\n  \nb",
+    "a\n\t\nb",
+    "carriage\r\nreturn",
+    "\r",
+    "\0",
+    "\u{7f}\u{80}\u{85}\u{1b}",
+    "\u{2028}",
+    "\u{feff}",
+    "\u{a0}",
+    "\\",
+    "日本語",
+    "😀",
+];
+
+#[test]
+fn scalars() {
+    let mut values = vec![
+        Value::Null,
+        Value::Boolean(true),
+        Value::Boolean(false),
+        Value::Int(0),
+        Value::Int(-1),
+        Value::Int(i128::MAX),
+        Value::Int(i128::MIN),
+        Value::Float(0.0),
+        Value::Float(-0.0),
+        Value::Float(1.0),
+        Value::Float(-1.5),
+        Value::Float(0.1),
+        Value::Float(1e300),
+        Value::Float(-1e-300),
+        Value::Float(5e-324),
+        Value::Float(f64::MAX),
+        Value::Float(f64::NAN),
+        Value::Float(f64::INFINITY),
+        Value::Float(f64::NEG_INFINITY),
+        Value::Array(Vec::new()),
+        Value::Map(Map::new()),
+    ];
+    values.extend(STRINGS.iter().map(|s| Value::String(s.to_string())));
+    values.extend(datetimes());
+
+    for generator in generators() {
+        for value in &values {
+            assert_round_trip(&generator, value);
+            assert_round_trip(&generator, &Value::Array(vec![value.clone()]));
+            assert_round_trip(&generator, &map(vec![("k", value.clone())]));
+            assert_round_trip(
+                &generator,
+                &map(vec![("k", map(vec![("k", value.clone())]))]),
+            );
+        }
+    }
+}
+
+#[test]
+fn keys() {
+    for generator in generators() {
+        let all = map(STRINGS
+            .iter()
+            .map(|k| (*k, Value::String(k.to_string())))
+            .collect());
+        assert_round_trip(&generator, &all);
+    }
+}
+
+#[test]
+fn block_style() {
+    let value = map(vec![
+        (
+            "spec",
+            map(vec![(
+                "containers",
+                Value::Array(vec![map(vec![
+                    ("name", Value::String("web".to_string())),
+                    ("image", Value::String("nginx:1.19".to_string())),
+                    (
+                        "args",
+                        Value::Array(vec![Value::String("--port".to_string())]),
+                    ),
+                ])]),
+            )]),
+        ),
+        ("version", Value::String("1.10".to_string())),
+        ("enabled", Value::String("yes".to_string())),
+        ("script", Value::String("echo a\necho b\n".to_string())),
+        (
+            "matrix",
+            Value::Array(vec![Value::Array(vec![Value::Int(1), Value::Int(2)])]),
+        ),
+        ("empty", Value::Array(Vec::new())),
+    ]);
+    assert_eq!(
+        generate(&YamlGenerator::new(), &value),
+        "spec:\n  \
+           containers:\n    \
+             - name: web\n      \
+               image: nginx:1.19\n      \
+               args:\n        \
+                 - --port\n\
+         version: \"1.10\"\n\
+         enabled: \"yes\"\n\
+         script: |\n  \
+           echo a\n  \
+           echo b\n\
+         matrix:\n  \
+           - - 1\n    \
+             - 2\n\
+         empty: []\n"
+    );
+}
+
+/// Characters of random strings.
+const CHARS: &[char] = &[
+    'a', 'Z', '0', '9', '.', '-', ' ', '"', '\'', '\\', ':', '#', '\n', '\r', '\t', '\0', '[', '}',
+    ',', 'é', '😀',
+];
+
+#[test]
+fn random_values() {
+    let datetimes = datetimes();
+    let mut rng = Rng::new(STRINGS, CHARS);
+    for generator in generators() {
+        for _ in 0..2000 {
+            let value = rng.value(4, &datetimes);
+            assert_round_trip(&generator, &value);
+        }
+    }
+}